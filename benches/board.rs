@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_tetris::ai::find_placements;
+use rust_tetris::config::{BOARD_HEIGHT, BOARD_WIDTH};
+use rust_tetris::{Board, GameState, Tetromino, TetrominoType};
+
+/// Board with a ragged stack in the bottom half so collision checks have
+/// something to hit.
+fn stacked_board() -> Board {
+    let mut board = Board::new();
+    for (i, x) in (0..BOARD_WIDTH - 2).step_by(2).enumerate() {
+        let mut piece = Tetromino::new(TetrominoType::O);
+        piece.x = x;
+        piece.y = BOARD_HEIGHT - 2 - (i as i32 % 3) * 2;
+        while board.is_valid_position(&piece) {
+            piece.y += 1;
+        }
+        piece.y -= 1;
+        board.place_piece(&piece);
+    }
+    board
+}
+
+fn bench_collision(c: &mut Criterion) {
+    let board = stacked_board();
+    let piece = Tetromino::new(TetrominoType::T);
+
+    c.bench_function("is_valid_position", |b| {
+        b.iter(|| board.is_valid_position(black_box(&piece)))
+    });
+}
+
+fn bench_ghost(c: &mut Criterion) {
+    let mut state = GameState::new();
+    state.board = stacked_board();
+
+    c.bench_function("get_ghost_piece", |b| {
+        b.iter(|| black_box(&state).get_ghost_piece())
+    });
+}
+
+fn bench_place_and_clear(c: &mut Criterion) {
+    c.bench_function("place_and_clear_two_lines", |b| {
+        b.iter(|| {
+            let mut board = Board::new();
+            for x in (0..BOARD_WIDTH).step_by(2) {
+                let mut piece = Tetromino::new(TetrominoType::O);
+                piece.x = x;
+                piece.y = BOARD_HEIGHT - 2;
+                board.place_piece(&piece);
+            }
+            black_box(board.clear_lines())
+        })
+    });
+}
+
+fn bench_find_placements(c: &mut Criterion) {
+    let board = stacked_board();
+    let piece = Tetromino::new(TetrominoType::T);
+
+    c.bench_function("find_placements", |b| {
+        b.iter(|| find_placements(black_box(&board), black_box(&piece)))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_collision,
+    bench_ghost,
+    bench_place_and_clear,
+    bench_find_placements
+);
+criterion_main!(benches);
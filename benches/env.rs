@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rust_tetris::env::{Action, Env, VecEnv};
+
+fn bench_placement_step(c: &mut Criterion) {
+    let mut env = Env::new();
+    let mut seed = 0;
+    env.reset(seed);
+
+    c.bench_function("env_placement_step", |b| {
+        b.iter(|| {
+            let (_, _, done, _) = env.step(black_box(Action::Place(0)));
+            if done {
+                seed += 1;
+                env.reset(seed);
+            }
+        })
+    });
+}
+
+fn bench_frame_step(c: &mut Criterion) {
+    let mut env = Env::new();
+    env.reset(0);
+
+    c.bench_function("env_frame_step", |b| {
+        b.iter(|| {
+            let (_, _, done, _) = env.step(black_box(Action::Frame(None)));
+            if done {
+                env.reset(0);
+            }
+        })
+    });
+}
+
+fn bench_batch_step(c: &mut Criterion) {
+    let count = 256;
+    let mut batch = VecEnv::new(count, Env::new);
+    batch.reset(&(0..count as u64).collect::<Vec<_>>());
+
+    let mut group = c.benchmark_group("vec_env");
+    group.throughput(Throughput::Elements(count as u64));
+    group.bench_function("placement_step_256", |b| {
+        b.iter(|| {
+            batch.run(|env, i| {
+                let (_, _, done, _) = env.step(black_box(Action::Place(0)));
+                if done {
+                    env.reset(i as u64 + env.state().stats.pieces_placed as u64);
+                }
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_placement_step,
+    bench_frame_step,
+    bench_batch_step
+);
+criterion_main!(benches);
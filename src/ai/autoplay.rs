@@ -0,0 +1,101 @@
+use super::heuristic::Bot;
+use crate::config::AUTOPLAY_RESTART_SECONDS;
+use crate::game::GameState;
+
+/// Plays a `GameState` with a `Bot` in place of the keyboard, for demos and
+/// attract screens. Each piece is placed in one go, at most `pps` pieces a
+/// second, and a finished game restarts after a short pause.
+pub struct AutoPlayer {
+    pub bot: Bot,
+    /// Pieces per second, or `None` to place each piece as soon as it spawns.
+    pub pps: Option<f32>,
+    wait: f32,
+}
+
+impl AutoPlayer {
+    pub fn new(bot: Bot, pps: Option<f32>) -> Self {
+        AutoPlayer {
+            bot,
+            pps: pps.filter(|&pps| pps > 0.0),
+            wait: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, state: &mut GameState, dt: f32) {
+        self.wait -= dt;
+
+        if state.game_over {
+            if self.wait <= -AUTOPLAY_RESTART_SECONDS {
+                state.reset();
+                self.wait = 0.0;
+            }
+            return;
+        }
+
+        if state.is_counting_down() || state.is_entry_delay() || self.wait > 0.0 {
+            return;
+        }
+
+        self.wait = match self.pps {
+            Some(pps) => self.wait.max(0.0) + 1.0 / pps,
+            None => 0.0,
+        };
+        self.play_piece(state);
+    }
+
+    /// Lock the current piece where the bot wants it, hard dropping in place
+    /// if it has nowhere to go.
+    pub fn play_piece(&self, state: &mut GameState) {
+        match self.bot.best_placement(&state.board, &state.current_piece) {
+            Some(placement) => {
+                for input in placement.inputs {
+                    state.apply_input(input);
+                }
+            }
+            None => state.hard_drop(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Outcome;
+
+    #[test]
+    fn test_bot_survives_and_clears_lines() {
+        let mut state = GameState::new();
+        let player = AutoPlayer::new(Bot::default(), None);
+
+        for _ in 0..300 {
+            player.play_piece(&mut state);
+        }
+        assert!(!state.game_over);
+        assert!(state.scoring.lines_cleared >= 100);
+    }
+
+    #[test]
+    fn test_pps_limit() {
+        let mut state = GameState::new();
+        let mut player = AutoPlayer::new(Bot::default(), Some(2.0));
+
+        for _ in 0..60 {
+            player.update(&mut state, 1.0 / 60.0);
+            state.update(1.0 / 60.0);
+        }
+        assert_eq!(state.stats.pieces_placed, 2);
+    }
+
+    #[test]
+    fn test_restarts_after_game_over() {
+        let mut state = GameState::new();
+        let mut player = AutoPlayer::new(Bot::default(), None);
+        state.game_over = true;
+        state.outcome = Some(Outcome::ToppedOut);
+
+        player.update(&mut state, AUTOPLAY_RESTART_SECONDS / 2.0);
+        assert!(state.game_over);
+        player.update(&mut state, AUTOPLAY_RESTART_SECONDS);
+        assert!(!state.game_over);
+    }
+}
@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use super::movegen::{find_placements, Placement};
+use crate::game::board::Board;
+use crate::tetromino::Tetromino;
+
+/// Board features a placement is judged by, after Dellacherie's evaluator
+/// as refined by El-Tetris. All are measured on the visible rows once the
+/// piece has locked and any lines have cleared.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Features {
+    /// Height of the middle of the locked piece above the floor.
+    pub landing_height: f32,
+    /// Lines cleared times the piece's own cells that went with them.
+    pub eroded_cells: f32,
+    /// Filled/empty changes along each row, walls counted as filled.
+    pub row_transitions: f32,
+    /// Filled/empty changes down each column, the floor counted as filled.
+    pub column_transitions: f32,
+    /// Empty cells with a block somewhere above them.
+    pub holes: f32,
+    /// Sum over every well of 1 + 2 + ... + depth.
+    pub wells: f32,
+    /// Sum of the column heights.
+    pub aggregate_height: f32,
+    /// Sum of the height differences between neighbouring columns.
+    pub bumpiness: f32,
+}
+
+impl Features {
+    /// Lock `piece` on a copy of `board` and measure the result.
+    pub fn measure(board: &Board, piece: &Tetromino) -> Self {
+        let mut board = board.clone();
+        board.place_piece(piece);
+
+        let height = board.height();
+        let full = board.full_row_mask();
+        let blocks = piece.get_blocks();
+        let mut full_rows: Vec<i32> = blocks
+            .iter()
+            .map(|&(_, y)| y)
+            .filter(|&y| y >= 0 && board.rows()[y as usize] == full)
+            .collect();
+        full_rows.sort_unstable();
+        full_rows.dedup();
+        let cleared_cells = blocks.iter().filter(|(_, y)| full_rows.contains(y)).count();
+        board.clear_lines();
+
+        let (top, bottom) = blocks
+            .iter()
+            .fold((i32::MAX, i32::MIN), |(top, bottom), &(_, y)| {
+                (top.min(y), bottom.max(y))
+            });
+
+        let mut features = Features {
+            landing_height: (height - 1) as f32 - (top + bottom) as f32 / 2.0,
+            eroded_cells: (full_rows.len() * cleared_cells) as f32,
+            ..Features::default()
+        };
+        features.measure_stack(&board);
+        features
+    }
+
+    fn measure_stack(&mut self, board: &Board) {
+        let (width, height) = (board.width(), board.height());
+        let full = board.full_row_mask();
+        let rows = board.rows();
+        // Rows widened by a filled wall on each side, which may not fit in
+        // a u32 on the widest boards.
+        let walls = 1 | 1 << (width + 1);
+        let edges = (full as u64) << 1 | 1;
+
+        let mut covered = 0u32;
+        let mut previous: Option<u32> = None;
+        for &row in rows {
+            let bordered = (row as u64) << 1 | walls;
+            self.row_transitions += ((bordered ^ bordered >> 1) & edges).count_ones() as f32;
+            if let Some(previous) = previous {
+                self.column_transitions += ((row ^ previous) & full).count_ones() as f32;
+            }
+            self.holes += (covered & !row & full).count_ones() as f32;
+            covered |= row;
+            previous = Some(row);
+        }
+        if let Some(last) = previous {
+            self.column_transitions += (!last & full).count_ones() as f32;
+        }
+
+        let filled = |x: i32, y: usize| !(0..width).contains(&x) || rows[y] & 1 << x != 0;
+        let mut heights = Vec::with_capacity(width as usize);
+        for x in 0..width {
+            let mut depth = 0;
+            for y in 0..rows.len() {
+                if !filled(x, y) && filled(x - 1, y) && filled(x + 1, y) {
+                    depth += 1;
+                    self.wells += depth as f32;
+                } else {
+                    depth = 0;
+                }
+            }
+            let top = (0..rows.len()).find(|&y| filled(x, y));
+            heights.push(top.map_or(0, |y| height - y as i32));
+        }
+
+        self.aggregate_height = heights.iter().sum::<i32>() as f32;
+        self.bumpiness = heights
+            .windows(2)
+            .map(|pair| (pair[0] - pair[1]).abs())
+            .sum::<i32>() as f32;
+    }
+}
+
+/// How much each feature counts toward a placement's score.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weights {
+    pub landing_height: f32,
+    pub eroded_cells: f32,
+    pub row_transitions: f32,
+    pub column_transitions: f32,
+    pub holes: f32,
+    pub wells: f32,
+    pub aggregate_height: f32,
+    pub bumpiness: f32,
+}
+
+impl Weights {
+    /// Feature names, in the order of `to_array`.
+    pub const NAMES: [&'static str; 8] = [
+        "landing_height",
+        "eroded_cells",
+        "row_transitions",
+        "column_transitions",
+        "holes",
+        "wells",
+        "aggregate_height",
+        "bumpiness",
+    ];
+
+    pub fn to_array(&self) -> [f32; 8] {
+        [
+            self.landing_height,
+            self.eroded_cells,
+            self.row_transitions,
+            self.column_transitions,
+            self.holes,
+            self.wells,
+            self.aggregate_height,
+            self.bumpiness,
+        ]
+    }
+
+    pub fn from_array(values: [f32; 8]) -> Self {
+        Weights {
+            landing_height: values[0],
+            eroded_cells: values[1],
+            row_transitions: values[2],
+            column_transitions: values[3],
+            holes: values[4],
+            wells: values[5],
+            aggregate_height: values[6],
+            bumpiness: values[7],
+        }
+    }
+
+    /// Read weights from a file in the format `Display` writes.
+    pub fn load(path: &str) -> Result<Self, String> {
+        std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())?
+            .parse()
+    }
+
+    pub fn score(&self, features: &Features) -> f32 {
+        self.landing_height * features.landing_height
+            + self.eroded_cells * features.eroded_cells
+            + self.row_transitions * features.row_transitions
+            + self.column_transitions * features.column_transitions
+            + self.holes * features.holes
+            + self.wells * features.wells
+            + self.aggregate_height * features.aggregate_height
+            + self.bumpiness * features.bumpiness
+    }
+}
+
+impl Default for Weights {
+    /// El-Tetris' published weights, which leave height and bumpiness to
+    /// the other features.
+    fn default() -> Self {
+        Weights {
+            landing_height: -4.500_159,
+            eroded_cells: 3.418_127,
+            row_transitions: -3.217_888,
+            column_transitions: -9.348_695,
+            holes: -7.899_265,
+            wells: -3.385_597,
+            aggregate_height: 0.0,
+            bumpiness: 0.0,
+        }
+    }
+}
+
+/// One `name: value` line per weight.
+impl fmt::Display for Weights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in Self::NAMES.iter().zip(self.to_array()) {
+            writeln!(f, "{name}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses `name: value` lines as `Display` writes them. Weights that are
+/// not named keep their defaults; blank lines and lines starting with `#`
+/// are ignored.
+impl FromStr for Weights {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut values = Weights::default().to_array();
+
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("line {}: {message}", number + 1);
+
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| error(format!("expected 'name: value', got '{line}'")))?;
+            let (name, value) = (name.trim(), value.trim());
+            let index = Self::NAMES
+                .iter()
+                .position(|&known| known == name)
+                .ok_or_else(|| error(format!("unknown weight '{name}'")))?;
+            values[index] = value
+                .parse()
+                .map_err(|_| error(format!("bad value '{value}' for {name}")))?;
+        }
+        Ok(Weights::from_array(values))
+    }
+}
+
+/// A one-piece-lookahead player that locks the current piece wherever the
+/// evaluator scores best.
+#[derive(Clone, Debug, Default)]
+pub struct Bot {
+    pub weights: Weights,
+}
+
+impl Bot {
+    pub fn new(weights: Weights) -> Self {
+        Bot { weights }
+    }
+
+    /// How good locking `placement` on `board` would be. Placements that
+    /// top out score negative infinity, below anything else.
+    pub fn evaluate(&self, board: &Board, placement: &Placement) -> f32 {
+        if placement.piece.y <= 0 {
+            return f32::NEG_INFINITY;
+        }
+        self.weights
+            .score(&Features::measure(board, &placement.piece))
+    }
+
+    /// Every placement for `piece` on `board` with its score, best first.
+    /// Ties keep the move generator's order, shortest inputs first.
+    pub fn ranked_placements(&self, board: &Board, piece: &Tetromino) -> Vec<(Placement, f32)> {
+        let mut ranked: Vec<_> = find_placements(board, piece)
+            .into_iter()
+            .map(|placement| {
+                let score = self.evaluate(board, &placement);
+                (placement, score)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        ranked
+    }
+
+    /// The best placement for `piece` on `board`, avoiding ones that would
+    /// top out while any other is available.
+    pub fn best_placement(&self, board: &Board, piece: &Tetromino) -> Option<Placement> {
+        self.ranked_placements(board, piece)
+            .into_iter()
+            .next()
+            .map(|(placement, _)| placement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::Block;
+    use crate::tetromino::TetrominoType;
+
+    fn board_from(rows: &[&str]) -> Board {
+        let mut board = Board::new();
+        let top = board.height() - rows.len() as i32;
+        for (dy, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                if cell == 'X' {
+                    board.set_block(x as i32, top + dy as i32, Some(Block::Garbage));
+                }
+            }
+        }
+        board
+    }
+
+    fn piece_at(piece_type: TetrominoType, rotation: u8, x: i32, y: i32) -> Tetromino {
+        Tetromino {
+            piece_type,
+            rotation,
+            x,
+            y,
+        }
+    }
+
+    #[test]
+    fn test_measure_stack_features() {
+        let board = board_from(&["X.........", "XX.X......", "XXXX.....X"]);
+        // An O piece in the two rightmost columns, resting on the lone
+        // block and covering a hole beside it.
+        let features = Features::measure(&board, &piece_at(TetrominoType::O, 0, 7, 17));
+
+        assert_eq!(features.holes, 1.0);
+        assert_eq!(features.aggregate_height, 3.0 + 2.0 + 1.0 + 2.0 + 3.0 + 3.0);
+        assert_eq!(features.bumpiness, 1.0 + 1.0 + 1.0 + 2.0 + 3.0);
+        assert_eq!(features.landing_height, 1.5);
+        assert_eq!(features.eroded_cells, 0.0);
+        // Column 2 is a one-deep well between columns 1 and 3.
+        assert_eq!(features.wells, 1.0);
+    }
+
+    #[test]
+    fn test_measure_counts_eroded_cells() {
+        let board = board_from(&["XXXXXX....", "XXXXXX...."]);
+        let features = Features::measure(&board, &piece_at(TetrominoType::I, 0, 6, 18));
+
+        // One line cleared, all four of the I's cells in it.
+        assert_eq!(features.eroded_cells, 4.0);
+        assert_eq!(features.aggregate_height, 6.0);
+    }
+
+    #[test]
+    fn test_weights_round_trip_through_text() {
+        let weights = Weights {
+            holes: -12.5,
+            bumpiness: -0.25,
+            ..Weights::default()
+        };
+        assert_eq!(weights.to_string().parse(), Ok(weights));
+
+        let partial: Weights = "# tuned\nholes: -3\n".parse().unwrap();
+        assert_eq!(partial.holes, -3.0);
+        assert_eq!(partial.wells, Weights::default().wells);
+
+        assert_eq!(
+            "height: 1".parse::<Weights>(),
+            Err("line 1: unknown weight 'height'".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_bot_fills_the_well() {
+        let board = board_from(&["XXXXXXXXX.", "XXXXXXXXX.", "XXXXXXXXX.", "XXXXXXXXX."]);
+        let placement = Bot::default()
+            .best_placement(&board, &Tetromino::new(TetrominoType::I))
+            .unwrap();
+        assert!(placement.cells().iter().all(|&(x, _)| x == 9));
+    }
+
+    #[test]
+    fn test_bot_avoids_holes() {
+        let board = board_from(&["XXXX.XXXXX"]);
+        let placement = Bot::default()
+            .best_placement(&board, &Tetromino::new(TetrominoType::T))
+            .unwrap();
+        assert!(placement.cells().contains(&(4, 19)));
+    }
+}
@@ -0,0 +1,133 @@
+use super::heuristic::Bot;
+use super::movegen::Placement;
+use crate::game::GameState;
+use crate::tetromino::{Tetromino, TetrominoType};
+
+/// How much of the bot's advice the coaching overlay shows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HintLevel {
+    #[default]
+    Off,
+    /// Outline where the bot would lock the current piece.
+    Placement,
+    /// The outline plus the piece's position after each key press, numbered.
+    Path,
+}
+
+impl HintLevel {
+    /// The level after this one, wrapping back to `Off`.
+    pub fn next(self) -> Self {
+        match self {
+            HintLevel::Off => HintLevel::Placement,
+            HintLevel::Placement => HintLevel::Path,
+            HintLevel::Path => HintLevel::Off,
+        }
+    }
+}
+
+/// The bot's recommendation for the piece in play.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    pub placement: Placement,
+    /// Where the piece is after each of `placement.inputs`, from spawn.
+    pub path: Vec<Tetromino>,
+}
+
+/// Keeps a `Hint` for the current piece, asking the bot again only when
+/// the piece or the stack changes: on spawn, after a hold and after undo.
+#[derive(Default)]
+pub struct Coach {
+    pub bot: Bot,
+    pub level: HintLevel,
+    /// What the hint was worked out for: the stack and the piece type.
+    key: Option<(Vec<u32>, TetrominoType)>,
+    hint: Option<Hint>,
+}
+
+impl Coach {
+    pub fn new(bot: Bot, level: HintLevel) -> Self {
+        Coach {
+            bot,
+            level,
+            key: None,
+            hint: None,
+        }
+    }
+
+    pub fn hint(&self) -> Option<&Hint> {
+        self.hint.as_ref()
+    }
+
+    /// Bring the hint up to date with `state`. Does nothing while hints are
+    /// off or nothing has changed since the last call.
+    pub fn update(&mut self, state: &GameState) {
+        if self.level == HintLevel::Off {
+            return;
+        }
+
+        let piece_type = state.current_piece.piece_type;
+        let unchanged = self
+            .key
+            .as_ref()
+            .is_some_and(|(rows, key_type)| *key_type == piece_type && rows == state.board.rows());
+        if unchanged {
+            return;
+        }
+
+        let board = &state.board;
+        let spawn = Tetromino::spawn(piece_type, board.width());
+        self.hint = self
+            .bot
+            .best_placement(board, &spawn)
+            .map(|placement| Hint {
+                path: placement.path(board, &spawn),
+                placement,
+            });
+        self.key = Some((board.rows().to_vec(), piece_type));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_follows_spawn_and_hold() {
+        let mut state = GameState::new();
+        let mut coach = Coach::new(Bot::default(), HintLevel::Placement);
+
+        coach.update(&state);
+        let first = coach.hint().cloned().expect("a hint on an empty board");
+        assert_eq!(
+            first.placement.piece.piece_type,
+            state.current_piece.piece_type
+        );
+        assert_eq!(first.path.last(), Some(&first.placement.piece));
+
+        // Moving the piece keeps the hint from spawn.
+        state.try_move(1, 0);
+        coach.update(&state);
+        assert_eq!(coach.hint(), Some(&first));
+
+        state.hold();
+        coach.update(&state);
+        let held = coach.hint().unwrap();
+        assert_eq!(
+            held.placement.piece.piece_type,
+            state.current_piece.piece_type
+        );
+
+        state.hard_drop();
+        state.update(1.0);
+        coach.update(&state);
+        assert_eq!(coach.key.as_ref().unwrap().0, state.board.rows());
+    }
+
+    #[test]
+    fn test_no_hint_while_off() {
+        let mut coach = Coach::default();
+        coach.update(&GameState::new());
+        assert!(coach.hint().is_none());
+        assert_eq!(HintLevel::Path.next(), HintLevel::Off);
+    }
+}
@@ -0,0 +1,13 @@
+pub mod autoplay;
+pub mod heuristic;
+pub mod hint;
+pub mod movegen;
+pub mod pc;
+pub mod tuner;
+
+pub use autoplay::AutoPlayer;
+pub use heuristic::{Bot, Features, Weights};
+pub use hint::{Coach, Hint, HintLevel};
+pub use movegen::{find_placements, Input, Placement};
+pub use pc::PcSolver;
+pub use tuner::{Generation, Tuner, TunerConfig};
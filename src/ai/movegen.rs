@@ -0,0 +1,527 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::game::board::Board;
+use crate::game::scoring::Spin;
+use crate::tetromino::{Tetromino, TetrominoType};
+
+/// One button press, as the game's input handler would apply it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Input {
+    Left,
+    Right,
+    /// Hold left until the piece stops against the wall or the stack.
+    DasLeft,
+    /// Hold right until the piece stops against the wall or the stack.
+    DasRight,
+    RotateCw,
+    RotateCcw,
+    /// Hold soft drop until the piece lands, without locking it.
+    SoftDrop,
+    HardDrop,
+}
+
+/// Every input the search tries from each position, in the order it
+/// prefers them when two sequences are equally short.
+const MOVES: [Input; 7] = [
+    Input::Left,
+    Input::Right,
+    Input::RotateCw,
+    Input::RotateCcw,
+    Input::DasLeft,
+    Input::DasRight,
+    Input::SoftDrop,
+];
+
+/// A resting position a piece can lock in, and how to get there.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+    /// The piece where it locks.
+    pub piece: Tetromino,
+    /// The T-spin this lock would score.
+    pub spin: Spin,
+    /// The shortest input sequence from the spawn position, ending with
+    /// the hard drop.
+    pub inputs: Vec<Input>,
+}
+
+impl Placement {
+    /// Board cells the piece covers, sorted so equal shapes compare equal
+    /// whatever rotation state produced them.
+    pub fn cells(&self) -> [(i32, i32); 4] {
+        sorted_cells(&self.piece)
+    }
+
+    /// Where the piece is after each input, starting from `start` on
+    /// `board`. Inputs that do nothing leave it where it was.
+    pub fn path(&self, board: &Board, start: &Tetromino) -> Vec<Tetromino> {
+        let mut current = *start;
+        self.inputs
+            .iter()
+            .map(|&input| {
+                if let Some((moved, _)) = apply(board, &current, input) {
+                    current = moved;
+                }
+                current
+            })
+            .collect()
+    }
+}
+
+/// A position in the search: the piece and the kick of its last rotation,
+/// which decides whether locking there is a spin.
+#[derive(Clone, Copy)]
+struct Node {
+    piece: Tetromino,
+    last_kick: Option<usize>,
+    parent: Option<(usize, Input)>,
+    depth: usize,
+}
+
+type NodeKey = (i32, i32, u8, Option<usize>);
+
+fn key(piece: &Tetromino, last_kick: Option<usize>) -> NodeKey {
+    (piece.x, piece.y, piece.rotation, last_kick)
+}
+
+/// Per-position tables for one search, covering the board and a margin
+/// around it, which are far cheaper than hashing or testing cells one by
+/// one. Anything kicked high above that falls back to the board and a set.
+struct Grid {
+    left: i32,
+    top: i32,
+    columns: i32,
+    rows: i32,
+    /// For each row and rotation, a bit per column (from `left`) the piece
+    /// fits at.
+    fits: Vec<u64>,
+    /// Reached positions, by slot and `scoring_kick`.
+    seen: Vec<bool>,
+    /// Positions pieces have locked in, likewise.
+    locked: Vec<bool>,
+    far: HashSet<NodeKey>,
+}
+
+impl Grid {
+    fn new(board: &Board, start: &Tetromino) -> Self {
+        let (left, top) = (-4, start.y.min(0) - 4);
+        let (columns, rows) = (board.width() + 8, board.height() - top);
+        let slots = (columns * rows * 4) as usize;
+
+        // Bits set where a cell is taken, counting the walls and floor,
+        // for the rows a piece in the grid can reach. Cells sit up to one
+        // row above a piece's position and three below it.
+        let blocked: Vec<u64> = (top - 1..top + rows + 4)
+            .map(|y| {
+                if y >= board.height() {
+                    return u64::MAX;
+                }
+                let free = board.full_row_mask() & !board.row(y);
+                !(u64::from(free) << -left)
+            })
+            .collect();
+        let cells = |rotation| start.piece_type.cells(rotation);
+        let fits = (0..rows as usize)
+            .flat_map(|y| (0..4).map(move |rotation| (y, rotation)))
+            .map(|(y, rotation)| {
+                let hit = cells(rotation).iter().fold(0, |hit, &(dx, dy)| {
+                    hit | blocked[(y as i32 + 1 + dy as i32) as usize] >> dx
+                });
+                !hit
+            })
+            .collect();
+
+        Grid {
+            left,
+            top,
+            columns,
+            rows,
+            fits,
+            seen: vec![false; slots * 3],
+            locked: vec![false; slots * 3],
+            far: HashSet::new(),
+        }
+    }
+
+    fn slot(&self, piece: &Tetromino) -> Option<usize> {
+        let (x, y) = (piece.x - self.left, piece.y - self.top);
+        ((0..self.columns).contains(&x) && (0..self.rows).contains(&y))
+            .then(|| ((y * self.columns + x) * 4 + piece.rotation as i32) as usize)
+    }
+
+    /// `Board::is_valid_position`, looked up.
+    fn fits(&self, board: &Board, piece: &Tetromino) -> bool {
+        let (x, y) = (piece.x - self.left, piece.y - self.top);
+        if !(0..self.columns).contains(&x) {
+            return false;
+        }
+        if !(0..self.rows).contains(&y) {
+            return board.is_valid_position(piece);
+        }
+        self.fits[y as usize * 4 + piece.rotation as usize] & 1 << x != 0
+    }
+
+    /// Mark a position and `scoring_kick` as reached, returning whether
+    /// it is new.
+    fn visit(&mut self, piece: &Tetromino, last_kick: Option<usize>) -> bool {
+        match self.index(piece, last_kick) {
+            Some(index) => !std::mem::replace(&mut self.seen[index], true),
+            None => self.far.insert(key(piece, last_kick)),
+        }
+    }
+
+    /// Whether no position locked so far has locked here, so many drops
+    /// that end in the same place are only compared once.
+    fn first_lock(&mut self, piece: &Tetromino, last_kick: Option<usize>) -> bool {
+        self.index(piece, last_kick)
+            .is_none_or(|index| !std::mem::replace(&mut self.locked[index], true))
+    }
+
+    fn index(&self, piece: &Tetromino, last_kick: Option<usize>) -> Option<usize> {
+        let kick = match last_kick {
+            None => 0,
+            Some(4) => 2,
+            Some(_) => 1,
+        };
+        self.slot(piece).map(|slot| slot * 3 + kick)
+    }
+
+    /// `apply`, with every position checked against the tables.
+    fn apply(
+        &self,
+        board: &Board,
+        piece: &Tetromino,
+        input: Input,
+    ) -> Option<(Tetromino, Option<usize>)> {
+        let (dx, dy) = match input {
+            Input::Left | Input::DasLeft => (-1, 0),
+            Input::Right | Input::DasRight => (1, 0),
+            Input::SoftDrop | Input::HardDrop => (0, 1),
+            Input::RotateCw | Input::RotateCcw => {
+                let mut rotated = *piece;
+                if input == Input::RotateCw {
+                    rotated.rotate();
+                } else {
+                    rotated.rotate_ccw();
+                }
+                let kicks = piece.piece_type.kicks(piece.rotation, rotated.rotation);
+                return kicks.iter().enumerate().find_map(|(index, &(dx, dy))| {
+                    let mut kicked = rotated;
+                    kicked.move_by(dx as i32, dy as i32);
+                    self.fits(board, &kicked).then_some((kicked, Some(index)))
+                });
+            }
+        };
+
+        let mut moved = *piece;
+        loop {
+            let mut next = moved;
+            next.move_by(dx, dy);
+            if !self.fits(board, &next) {
+                break;
+            }
+            moved = next;
+            if matches!(input, Input::Left | Input::Right) {
+                break;
+            }
+        }
+        (moved != *piece).then_some((moved, None))
+    }
+}
+
+/// The part of a rotation's kick that can change how a lock scores. Only
+/// T pieces spin, and of the kicks only the last one makes a mini a full
+/// spin, so positions that differ otherwise are searched once.
+fn scoring_kick(piece: &Tetromino, kick: Option<usize>) -> Option<usize> {
+    if piece.piece_type != TetrominoType::T {
+        return None;
+    }
+    kick.map(|kick| if kick == 4 { 4 } else { 0 })
+}
+
+fn sorted_cells(piece: &Tetromino) -> [(i32, i32); 4] {
+    let mut cells = piece.get_blocks();
+    cells.sort_unstable();
+    cells
+}
+
+/// Slide `piece` by `dx`/`dy` until it hits something, returning how far
+/// it went.
+fn slide(board: &Board, piece: &mut Tetromino, dx: i32, dy: i32) -> u32 {
+    let mut steps = 0;
+    loop {
+        let mut next = *piece;
+        next.move_by(dx, dy);
+        if !board.is_valid_position(&next) {
+            return steps;
+        }
+        *piece = next;
+        steps += 1;
+    }
+}
+
+/// Apply one input to a piece that is not yet locked, returning where it
+/// ends up and the kick it used, or `None` if the input does nothing.
+fn apply(board: &Board, piece: &Tetromino, input: Input) -> Option<(Tetromino, Option<usize>)> {
+    let mut moved = *piece;
+    match input {
+        Input::Left | Input::Right => {
+            moved.move_by(if input == Input::Left { -1 } else { 1 }, 0);
+            board.is_valid_position(&moved).then_some((moved, None))
+        }
+        Input::DasLeft | Input::DasRight => {
+            let dx = if input == Input::DasLeft { -1 } else { 1 };
+            (slide(board, &mut moved, dx, 0) > 0).then_some((moved, None))
+        }
+        Input::SoftDrop | Input::HardDrop => {
+            (slide(board, &mut moved, 0, 1) > 0).then_some((moved, None))
+        }
+        Input::RotateCw | Input::RotateCcw => board
+            .try_rotate(piece, input == Input::RotateCw)
+            .map(|(rotated, kick)| (rotated, Some(kick))),
+    }
+}
+
+/// Every distinct place `piece` can lock on `board`, reachable from its
+/// current position with the game's movement and SRS rotation rules,
+/// including soft-drop tucks and kicked spins.
+///
+/// Gravity is ignored: the piece only falls when dropped, which is how a
+/// player with quick enough hands sees it. Placements that cover the same
+/// cells with the same spin are merged, keeping the shortest sequence, so
+/// the symmetric rotations of O, I, S and Z appear once. The result is in
+/// order of sequence length.
+pub fn find_placements(board: &Board, piece: &Tetromino) -> Vec<Placement> {
+    if !board.is_valid_position(piece) {
+        return Vec::new();
+    }
+
+    let mut nodes = vec![Node {
+        piece: *piece,
+        last_kick: None,
+        parent: None,
+        depth: 0,
+    }];
+    let mut grid = Grid::new(board, piece);
+    grid.visit(piece, None);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(index) = queue.pop_front() {
+        let node = nodes[index];
+        for input in MOVES {
+            let Some((moved, last_kick)) = grid.apply(board, &node.piece, input) else {
+                continue;
+            };
+            let last_kick = scoring_kick(&moved, last_kick);
+            if grid.visit(&moved, last_kick) {
+                queue.push_back(nodes.len());
+                nodes.push(Node {
+                    piece: moved,
+                    last_kick,
+                    parent: Some((index, input)),
+                    depth: node.depth + 1,
+                });
+            }
+        }
+    }
+
+    // Hard dropping from any reached position locks the piece; nodes are
+    // in order of depth, so the first one to reach a placement is the
+    // shortest route there.
+    let mut found: Vec<(Tetromino, Spin, usize)> = Vec::new();
+    let mut locks = HashSet::new();
+    for (index, node) in nodes.iter().enumerate() {
+        let (landed, last_kick) = grid
+            .apply(board, &node.piece, Input::HardDrop)
+            .unwrap_or((node.piece, node.last_kick));
+        if !grid.first_lock(&landed, last_kick) {
+            continue;
+        }
+        let spin = board.detect_spin(&landed, last_kick);
+        if locks.insert((sorted_cells(&landed), spin)) {
+            found.push((landed, spin, index));
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|(landed, spin, index)| Placement {
+            piece: landed,
+            spin,
+            inputs: inputs_to(&nodes, index),
+        })
+        .collect()
+}
+
+fn inputs_to(nodes: &[Node], mut index: usize) -> Vec<Input> {
+    let mut inputs = vec![Input::HardDrop];
+    while let Some((parent, input)) = nodes[index].parent {
+        inputs.push(input);
+        index = parent;
+    }
+    inputs.reverse();
+    inputs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::Block;
+    use crate::tetromino::TetrominoType;
+
+    /// Replay a placement's inputs the way `apply` does and check it lands
+    /// where the generator said it would.
+    fn replay(board: &Board, piece: &Tetromino, placement: &Placement) -> Tetromino {
+        let mut current = *piece;
+        for &input in &placement.inputs {
+            if let Some((moved, _)) = apply(board, &current, input) {
+                current = moved;
+            }
+        }
+        current
+    }
+
+    fn fill(board: &mut Board, cells: &[(i32, i32)]) {
+        for &(x, y) in cells {
+            board.set_block(x, y, Some(Block::Garbage));
+        }
+    }
+
+    #[test]
+    fn test_flat_board_placement_counts() {
+        let board = Board::new();
+        let counts = [
+            (TetrominoType::I, 17),
+            (TetrominoType::O, 9),
+            (TetrominoType::T, 34),
+            (TetrominoType::S, 17),
+            (TetrominoType::Z, 17),
+            (TetrominoType::J, 34),
+            (TetrominoType::L, 34),
+        ];
+        for (piece_type, count) in counts {
+            let placements = find_placements(&board, &Tetromino::new(piece_type));
+            assert_eq!(placements.len(), count, "{piece_type:?}");
+        }
+    }
+
+    #[test]
+    fn test_inputs_reach_the_placement() {
+        let board = Board::new();
+        let piece = Tetromino::new(TetrominoType::L);
+        for placement in find_placements(&board, &piece) {
+            assert_eq!(placement.inputs.last(), Some(&Input::HardDrop));
+            assert_eq!(
+                sorted_cells(&replay(&board, &piece, &placement)),
+                placement.cells()
+            );
+        }
+    }
+
+    #[test]
+    fn test_path_follows_the_inputs() {
+        let board = Board::new();
+        let piece = Tetromino::new(TetrominoType::T);
+        let placement = Placement {
+            piece,
+            spin: Spin::None,
+            inputs: vec![
+                Input::Left,
+                Input::RotateCw,
+                Input::DasRight,
+                Input::HardDrop,
+            ],
+        };
+
+        let path = placement.path(&board, &piece);
+        assert_eq!(path.len(), 4);
+        assert_eq!(path[0].x, piece.x - 1);
+        assert_eq!(path[1].rotation, 1);
+        assert_eq!(path[2].y, piece.y);
+        let rightmost = path[2].get_blocks().iter().map(|&(x, _)| x).max();
+        assert_eq!(rightmost, Some(board.width() - 1));
+        assert_eq!(path[3].y, board.height() - 3);
+    }
+
+    #[test]
+    fn test_shortest_sequences() {
+        let board = Board::new();
+        let piece = Tetromino::new(TetrominoType::O);
+        let placements = find_placements(&board, &piece);
+
+        assert_eq!(placements[0].inputs, vec![Input::HardDrop]);
+        let wall = placements
+            .iter()
+            .find(|placement| placement.piece.x == -1)
+            .unwrap();
+        assert_eq!(wall.inputs, vec![Input::DasLeft, Input::HardDrop]);
+    }
+
+    #[test]
+    fn test_soft_drop_tuck_under_overhang() {
+        let mut board = Board::new();
+        let bottom = board.height() - 1;
+        // A roof over the four leftmost columns of the bottom row.
+        fill(
+            &mut board,
+            &[
+                (0, bottom - 1),
+                (1, bottom - 1),
+                (2, bottom - 1),
+                (3, bottom - 1),
+            ],
+        );
+
+        let placements = find_placements(&board, &Tetromino::new(TetrominoType::I));
+        let tuck = placements
+            .iter()
+            .find(|placement| {
+                placement.cells() == [(0, bottom), (1, bottom), (2, bottom), (3, bottom)]
+            })
+            .expect("I piece slides under the roof");
+        assert!(tuck.inputs.contains(&Input::SoftDrop));
+    }
+
+    #[test]
+    fn test_t_spin_double_slot() {
+        let mut board = Board::new();
+        let bottom = board.height() - 1;
+        // Standard T-spin double: a one-wide hole at column 1 in the bottom
+        // row, a three-wide gap above it, and an overhang at column 0.
+        for x in 0..10 {
+            if x != 1 {
+                fill(&mut board, &[(x, bottom)]);
+            }
+            if !(0..3).contains(&x) {
+                fill(&mut board, &[(x, bottom - 1)]);
+            }
+        }
+        fill(&mut board, &[(0, bottom - 2)]);
+
+        let placements = find_placements(&board, &Tetromino::new(TetrominoType::T));
+        let spin = placements
+            .iter()
+            .find(|placement| placement.spin == Spin::Full)
+            .expect("T-spin double is reachable");
+        assert_eq!(
+            spin.cells(),
+            [
+                (0, bottom - 1),
+                (1, bottom - 1),
+                (1, bottom),
+                (2, bottom - 1)
+            ]
+        );
+        assert!(matches!(
+            spin.inputs[spin.inputs.len() - 2],
+            Input::RotateCw | Input::RotateCcw
+        ));
+    }
+
+    #[test]
+    fn test_blocked_spawn_has_no_placements() {
+        let mut board = Board::new();
+        let piece = Tetromino::new(TetrominoType::T);
+        fill(&mut board, &piece.get_blocks());
+        assert!(find_placements(&board, &piece).is_empty());
+    }
+}
@@ -0,0 +1,536 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::game::board::{Block, Board};
+use crate::tetromino::{Tetromino, TetrominoType};
+
+/// Searches for perfect clears: sequences of placements that leave the
+/// board completely empty, with every piece kept within the bottom
+/// `height` rows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PcSolver {
+    pub height: i32,
+    /// Positions to search before giving up as if there were no clear,
+    /// or `None` to search them all.
+    pub budget: Option<u32>,
+}
+
+impl PcSolver {
+    pub fn new(height: i32) -> Self {
+        PcSolver {
+            height,
+            budget: None,
+        }
+    }
+
+    /// A solver that gives up after searching `positions` positions.
+    pub fn with_budget(self, positions: u32) -> Self {
+        PcSolver {
+            budget: Some(positions),
+            ..self
+        }
+    }
+
+    /// A perfect clear from `board` using pieces from `queue`, the one in
+    /// play first, and `hold`, as the pieces in the order they lock.
+    /// Holding works as in the game: the held piece swaps with the one in
+    /// play, or the next one comes out if nothing is held yet.
+    pub fn solve(
+        &self,
+        board: &Board,
+        queue: &[TetrominoType],
+        hold: Option<TetrominoType>,
+    ) -> Option<Vec<Tetromino>> {
+        self.search(board, queue, hold, None).0
+    }
+
+    /// As `solve`, but the pieces must build `setup` before any line is
+    /// cleared. Pieces can go elsewhere on the way, but one that touches
+    /// `setup` has to fill four of its own type's cells exactly.
+    pub fn solve_through(
+        &self,
+        board: &Board,
+        queue: &[TetrominoType],
+        hold: Option<TetrominoType>,
+        setup: &Board,
+    ) -> Option<Vec<Tetromino>> {
+        self.search(board, queue, hold, Some(setup)).0
+    }
+
+    /// Whether there is surely no perfect clear that `solve`, or
+    /// `solve_through` if `setup` is given, would find. A search that runs
+    /// out of budget first rules nothing out.
+    pub fn rules_out(
+        &self,
+        board: &Board,
+        queue: &[TetrominoType],
+        hold: Option<TetrominoType>,
+        setup: Option<&Board>,
+    ) -> bool {
+        let (solution, gave_up) = self.search(board, queue, hold, setup);
+        solution.is_none() && !gave_up
+    }
+
+    /// The clear found, if any, and whether the search ran out of budget.
+    fn search(
+        &self,
+        board: &Board,
+        queue: &[TetrominoType],
+        hold: Option<TetrominoType>,
+        setup: Option<&Board>,
+    ) -> (Option<Vec<Tetromino>>, bool) {
+        let top = board.height() - self.height;
+        if !(0..=board.height()).contains(&top) || board.is_buffer_occupied() {
+            return (None, false);
+        }
+        let (above, rows) = board.rows().split_at(top as usize);
+        if above.iter().any(|&row| row != 0) {
+            return (None, false);
+        }
+        // A block on the setup other than its own can never be built over.
+        if setup.is_some_and(|setup| clashes(board, setup, top)) {
+            return (None, false);
+        }
+
+        let mut empty = board.clone();
+        empty.clear();
+        let mut search = Search {
+            empty,
+            height: rows.len(),
+            queue,
+            setup,
+            failed: HashSet::new(),
+            placements: HashMap::new(),
+            path: Vec::new(),
+            budget: self.budget,
+        };
+        let solution = search.run(rows, 0, hold).then_some(search.path);
+        (solution, search.budget == Some(0))
+    }
+}
+
+/// Whether `board` holds a block, from row `top` down, where `setup` has a
+/// different one.
+fn clashes(board: &Board, setup: &Board, top: i32) -> bool {
+    (top..board.height()).any(|y| {
+        (0..board.width()).any(|x| {
+            let wanted = setup.get_block(x, y);
+            wanted.is_some() && board.has_block(x, y) && board.get_block(x, y) != wanted
+        })
+    })
+}
+
+/// One depth-first search over the rows still to clear, occupancy masks
+/// top first, remembering the positions that led nowhere.
+struct Search<'a> {
+    /// An empty board the size of the real one.
+    empty: Board,
+    /// Rows to clear at the start.
+    height: usize,
+    queue: &'a [TetrominoType],
+    setup: Option<&'a Board>,
+    failed: HashSet<(Vec<u32>, usize, Option<char>)>,
+    /// Placements already found on each stack, which many orders of the
+    /// same pieces lead to.
+    placements: HashMap<(Vec<u32>, char), Vec<Tetromino>>,
+    path: Vec<Tetromino>,
+    /// Positions left to search, if limited.
+    budget: Option<u32>,
+}
+
+impl Search<'_> {
+    /// Whether `rows` can be cleared with `queue[next..]` still to come
+    /// and `hold` held.
+    fn run(&mut self, rows: &[u32], next: usize, hold: Option<TetrominoType>) -> bool {
+        if rows.is_empty() {
+            return true;
+        }
+        let pieces = self.queue.len().saturating_sub(next) + hold.is_some() as usize;
+        if !can_fill(rows, self.empty.width(), pieces) {
+            return false;
+        }
+        let key = (rows.to_vec(), next, hold.map(|piece| piece.letter()));
+        if self.failed.contains(&key) {
+            return false;
+        }
+        if let Some(budget) = &mut self.budget {
+            if *budget == 0 {
+                return false;
+            }
+            *budget -= 1;
+        }
+
+        // Nothing clears until the setup is built, so it lines up with the
+        // rows for as long as it matters.
+        let building = self
+            .setup
+            .filter(|setup| rows.len() == self.height && !is_built(rows, setup));
+        let top = self.empty.height() - rows.len() as i32;
+        let full_row = self.empty.full_row_mask();
+        for (piece_type, next, hold) in choices(self.queue, next, hold) {
+            let placements = match self.placements.get(&(rows.to_vec(), piece_type.letter())) {
+                Some(placements) => placements.clone(),
+                None => {
+                    let placements = self.find(rows, piece_type, building);
+                    self.placements
+                        .insert((rows.to_vec(), piece_type.letter()), placements.clone());
+                    placements
+                }
+            };
+
+            for piece in placements {
+                let mut after = rows.to_vec();
+                for (x, y) in piece.get_blocks() {
+                    after[(y - top) as usize] |= 1 << x;
+                }
+                after.retain(|&row| row != full_row);
+                if building.is_some() && after.len() < rows.len() {
+                    continue;
+                }
+
+                self.path.push(piece);
+                if self.run(&after, next, hold) {
+                    return true;
+                }
+                self.path.pop();
+            }
+        }
+        self.failed.insert(key);
+        false
+    }
+
+    /// Distinct places a piece of `piece_type` can lock within `rows`.
+    /// While `setup` is being built, a piece either stays off its blocks
+    /// entirely or lands exactly on its own type's.
+    fn find(
+        &self,
+        rows: &[u32],
+        piece_type: TetrominoType,
+        setup: Option<&Board>,
+    ) -> Vec<Tetromino> {
+        // Nothing stands above the rows being cleared, so starting just over
+        // them reaches the same places as starting from spawn, far quicker.
+        let top = self.empty.height() - rows.len() as i32;
+        let mut start = Tetromino::spawn(piece_type, self.empty.width());
+        start.move_by(0, (top - 1 - lowest(&start)).max(0));
+
+        let landed = if has_overhang(rows) {
+            let mut board = self.empty.clone();
+            for (dy, &row) in rows.iter().enumerate() {
+                for x in (0..board.width()).filter(|x| row & 1 << x != 0) {
+                    board.set_block(x, top + dy as i32, Some(Block::Garbage));
+                }
+            }
+            landings(&board, &start)
+        } else {
+            drops(rows, self.empty.width(), top, &start)
+        };
+
+        let mut placements: Vec<Tetromino> = Vec::new();
+        let mut shapes = Vec::new();
+        for piece in landed {
+            let cells = piece.get_blocks();
+            if cells.iter().any(|&(_, y)| y < top) {
+                continue;
+            }
+            if let Some(setup) = setup {
+                let own = Some(Block::Piece(piece_type));
+                let covered = cells.iter().filter(|&&(x, y)| setup.has_block(x, y));
+                let all_own = cells.iter().all(|&(x, y)| setup.get_block(x, y) == own);
+                if covered.count() > 0 && !all_own {
+                    continue;
+                }
+            }
+
+            let mut shape = cells;
+            shape.sort_unstable();
+            if !shapes.contains(&shape) {
+                shapes.push(shape);
+                placements.push(piece);
+            }
+        }
+        placements
+    }
+}
+
+/// The pieces that could lock next, each with where the queue and hold
+/// stand afterwards: the one in play, or whatever holding brings out.
+fn choices(
+    queue: &[TetrominoType],
+    next: usize,
+    hold: Option<TetrominoType>,
+) -> Vec<(TetrominoType, usize, Option<TetrominoType>)> {
+    let Some(&current) = queue.get(next) else {
+        return hold.map(|held| (held, next, None)).into_iter().collect();
+    };
+
+    let mut choices = vec![(current, next + 1, hold)];
+    match hold {
+        Some(held) if held != current => choices.push((held, next + 1, Some(current))),
+        None => {
+            if let Some(&after) = queue.get(next + 1).filter(|&&after| after != current) {
+                choices.push((after, next + 2, Some(current)));
+            }
+        }
+        _ => {}
+    }
+    choices
+}
+
+/// The row of a piece's lowest cell.
+fn lowest(piece: &Tetromino) -> i32 {
+    piece
+        .get_blocks()
+        .iter()
+        .map(|&(_, y)| y)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Whether any block in `rows` has an empty cell below it.
+fn has_overhang(rows: &[u32]) -> bool {
+    rows.windows(2).any(|pair| pair[0] & !pair[1] != 0)
+}
+
+/// Every place `start` lands by turning, shifting and dropping straight
+/// down onto `rows`, the bottom rows of the board from `top` down. With
+/// nothing overhanging, nowhere else can be reached, and finding them this
+/// way is much cheaper than searching.
+fn drops(rows: &[u32], width: i32, top: i32, start: &Tetromino) -> Vec<Tetromino> {
+    let fits = |piece: &Tetromino| {
+        piece.get_blocks().iter().all(|&(x, y)| {
+            let row = y - top;
+            (0..width).contains(&x)
+                && row < rows.len() as i32
+                && (row < 0 || rows[row as usize] & 1 << x == 0)
+        })
+    };
+
+    let mut landed = Vec::new();
+    for rotation in 0..4 {
+        let mut turned = *start;
+        turned.rotation = rotation;
+        turned.move_by(0, lowest(start) - lowest(&turned));
+
+        for x in -3..width {
+            let mut piece = turned;
+            piece.x = x;
+            if !fits(&piece) {
+                continue;
+            }
+            loop {
+                let mut lower = piece;
+                lower.move_by(0, 1);
+                if !fits(&lower) {
+                    break;
+                }
+                piece = lower;
+            }
+            landed.push(piece);
+        }
+    }
+    landed
+}
+
+/// Every position `start` can reach with shifts, soft drops and SRS
+/// rotations where it rests on something. Unlike `find_placements` this
+/// keeps no record of how each was reached, which the solver does not
+/// need and could not afford.
+fn landings(board: &Board, start: &Tetromino) -> Vec<Tetromino> {
+    if !board.is_valid_position(start) {
+        return Vec::new();
+    }
+
+    // Pieces reach at most a few cells past the walls and floor and above
+    // where they start, so a flat grid covers every position.
+    let (left, top) = (-4, start.y - 4);
+    let (columns, rows) = (board.width() + 8, board.height() - top + 4);
+    let index = |piece: &Tetromino| {
+        let (x, y) = (piece.x - left, piece.y - top);
+        ((0..columns).contains(&x) && (0..rows).contains(&y))
+            .then(|| ((y * columns + x) * 4 + piece.rotation as i32) as usize)
+    };
+    let mut seen = vec![false; (columns * rows * 4) as usize];
+    let mut stack = vec![*start];
+    if let Some(start) = index(start) {
+        seen[start] = true;
+    }
+
+    let mut landed = Vec::new();
+    while let Some(piece) = stack.pop() {
+        let mut moves = Vec::with_capacity(5);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, 1)] {
+            let mut moved = piece;
+            moved.move_by(dx, dy);
+            if board.is_valid_position(&moved) {
+                moves.push(moved);
+            } else if dy == 1 {
+                landed.push(piece);
+            }
+        }
+        for clockwise in [true, false] {
+            if let Some((rotated, _)) = board.try_rotate(&piece, clockwise) {
+                moves.push(rotated);
+            }
+        }
+
+        for moved in moves {
+            if let Some(slot) = index(&moved).filter(|&slot| !seen[slot]) {
+                seen[slot] = true;
+                stack.push(moved);
+            }
+        }
+    }
+    landed
+}
+
+/// Whether every block of `setup` in the bottom rows is in `rows`.
+fn is_built(rows: &[u32], setup: &Board) -> bool {
+    let wanted = &setup.rows()[setup.rows().len() - rows.len()..];
+    rows.iter()
+        .zip(wanted)
+        .all(|(&row, &wanted)| row & wanted == wanted)
+}
+
+/// Whether the empty cells of `rows` could still be filled exactly by
+/// `pieces` pieces: four cells each, and every enclosed pocket a multiple
+/// of four.
+fn can_fill(rows: &[u32], width: i32, pieces: usize) -> bool {
+    let full_row = u32::MAX >> (32 - width);
+    let empty: u32 = rows.iter().map(|&row| (!row & full_row).count_ones()).sum();
+    if !empty.is_multiple_of(4) || empty as usize / 4 > pieces {
+        return false;
+    }
+
+    let height = rows.len() as i32;
+    let mut visited = rows.to_vec();
+    for start_y in 0..height {
+        for start_x in 0..width {
+            if visited[start_y as usize] & 1 << start_x != 0 {
+                continue;
+            }
+            visited[start_y as usize] |= 1 << start_x;
+            let mut stack = vec![(start_x, start_y)];
+            let mut size = 0;
+            while let Some((x, y)) = stack.pop() {
+                size += 1;
+                for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                    let inside = (0..width).contains(&nx) && (0..height).contains(&ny);
+                    if inside && visited[ny as usize] & 1 << nx == 0 {
+                        visited[ny as usize] |= 1 << nx;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            if size % 4 != 0 {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TetrominoType::*;
+
+    fn board_from(rows: &[&str]) -> Board {
+        let mut board = Board::new();
+        let top = board.height() - rows.len() as i32;
+        for (dy, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                let block = match cell {
+                    '.' => None,
+                    'X' => Some(Block::Garbage),
+                    letter => TetrominoType::from_letter(letter).map(Block::Piece),
+                };
+                board.set_block(x as i32, top + dy as i32, block);
+            }
+        }
+        board
+    }
+
+    fn replay(board: &Board, pieces: &[Tetromino]) -> Board {
+        let mut board = board.clone();
+        for piece in pieces {
+            assert!(board.is_valid_position(piece));
+            board.place_piece(piece);
+            board.clear_lines();
+        }
+        board
+    }
+
+    #[test]
+    fn test_solves_a_two_line_clear() {
+        let board = board_from(&["XXXXXX....", "XXXXXX...."]);
+        let solution = PcSolver::new(2).solve(&board, &[O, O], None).unwrap();
+        assert_eq!(solution.len(), 2);
+        assert!(replay(&board, &solution).is_empty());
+
+        assert_eq!(PcSolver::new(2).solve(&board, &[T, S], None), None);
+    }
+
+    #[test]
+    fn test_uses_hold() {
+        let board = board_from(&["XXXXXXXX..", "XXXXXXXX.."]);
+        // The S cannot go anywhere, so the O has to come out of hold.
+        let solution = PcSolver::new(2).solve(&board, &[S, O], None).unwrap();
+        assert_eq!(solution[0].piece_type, O);
+        assert_eq!(
+            PcSolver::new(2).solve(&board, &[S], Some(O)).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rejects_odd_pockets_and_high_stacks() {
+        let pocket = board_from(&["X.XXXXXX..", "XXXXXXXX.."]);
+        assert_eq!(PcSolver::new(2).solve(&pocket, &[O, O, O], None), None);
+
+        let high = board_from(&["X.........", "..........", "XXXXXX...."]);
+        assert_eq!(PcSolver::new(2).solve(&high, &[O, O, O], None), None);
+    }
+
+    #[test]
+    fn test_rules_out_only_after_a_full_search() {
+        let pocket = board_from(&["X.XXXXXX..", "XXXXXXXX.."]);
+        assert!(PcSolver::new(2).rules_out(&pocket, &[O, O, O], None, None));
+        let queue = [T, S, Z, J, L, T, S, Z, J, L];
+        let empty = Board::new();
+        assert!(!PcSolver::new(4)
+            .with_budget(1)
+            .rules_out(&empty, &queue, None, None));
+    }
+
+    #[test]
+    fn test_wrong_piece_on_the_setup_is_ruled_out_at_once() {
+        let setup = board_from(&["IIII......"]);
+        let mut board = Board::new();
+        board.set_block(0, board.height() - 1, Some(Block::Piece(O)));
+        let solver = PcSolver::new(4).with_budget(1);
+        assert!(solver.rules_out(&board, &[I, O, O], None, Some(&setup)));
+    }
+
+    #[test]
+    fn test_first_bag_perfect_clear() {
+        let queue = [I, O, T, S, Z, J, L, I, O, T];
+        let board = Board::new();
+        let solution = PcSolver::new(4).solve(&board, &queue, None).unwrap();
+        assert_eq!(solution.len(), 10);
+        assert!(replay(&board, &solution).is_empty());
+    }
+
+    #[test]
+    fn test_solve_through_builds_the_setup_first() {
+        let setup = board_from(&["IIII......", "OO........", "OO........"]);
+        let board = Board::new();
+        let queue = [O, I, O, O, O, O, O, I, I, I, I, I];
+        let solver = PcSolver::new(4);
+
+        let solution = solver.solve_through(&board, &queue, None, &setup);
+        let solution = solution.expect("a clear through the setup");
+        let built = replay(&board, &solution[..2]);
+        assert!(is_built(built.rows(), &setup));
+        assert!(replay(&board, &solution).is_empty());
+    }
+}
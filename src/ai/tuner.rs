@@ -0,0 +1,241 @@
+use std::f32::consts::TAU;
+
+use macroquad::rand::RandGenerator;
+
+use super::heuristic::{Bot, Weights};
+use crate::config::{
+    TUNER_ELITES, TUNER_GAMES, TUNER_MAX_PIECES, TUNER_NOISE, TUNER_POPULATION, TUNER_SPREAD,
+};
+use crate::env::{Action, Env, Reward, VecEnv};
+
+/// How the tuner searches.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TunerConfig {
+    /// Weight sets tried each generation.
+    pub population: usize,
+    /// How many of the best sets the next generation is drawn around.
+    pub elites: usize,
+    /// Games each set plays per generation, every set on the same seeds.
+    pub games: usize,
+    /// Pieces after which a game is cut short, as good weights rarely top
+    /// out on their own.
+    pub max_pieces: u32,
+    /// What a game is scored by.
+    pub fitness: Reward,
+    /// Seed for sampling weights and dealing each generation's games.
+    pub seed: u64,
+    /// Starting standard deviation of each weight.
+    pub spread: f32,
+    /// Variance added back to each weight every generation, so the search
+    /// does not settle too early.
+    pub noise: f32,
+}
+
+impl Default for TunerConfig {
+    fn default() -> Self {
+        TunerConfig {
+            population: TUNER_POPULATION,
+            elites: TUNER_ELITES,
+            games: TUNER_GAMES,
+            max_pieces: TUNER_MAX_PIECES,
+            fitness: Reward::Lines,
+            seed: 0,
+            spread: TUNER_SPREAD,
+            noise: TUNER_NOISE,
+        }
+    }
+}
+
+/// How one generation went. Fitness is the mean reward per game.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Generation {
+    /// Counting from 1.
+    pub number: usize,
+    pub best: Weights,
+    pub best_fitness: f32,
+    pub mean_fitness: f32,
+}
+
+/// Tunes `Weights` by the cross-entropy method: each generation samples a
+/// population from a normal distribution per weight, plays every set on the
+/// same seeded headless games, and refits the distribution to the elites.
+pub struct Tuner {
+    pub config: TunerConfig,
+    mean: [f32; 8],
+    spread: [f32; 8],
+    rng: RandGenerator,
+    generation: usize,
+    best: Option<(Weights, f32)>,
+    envs: VecEnv,
+}
+
+impl Tuner {
+    /// A tuner whose first generation is drawn around `start`.
+    pub fn new(config: TunerConfig, start: Weights) -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(config.seed);
+        let envs = VecEnv::new(config.population * config.games, || {
+            Env::new().with_reward(config.fitness)
+        });
+
+        Tuner {
+            config,
+            mean: start.to_array(),
+            spread: [config.spread; 8],
+            rng,
+            generation: 0,
+            best: None,
+            envs,
+        }
+    }
+
+    /// The fittest weights of any generation so far, and their fitness.
+    pub fn best(&self) -> Option<(Weights, f32)> {
+        self.best
+    }
+
+    /// Sample, play and refit one generation.
+    pub fn step(&mut self) -> Generation {
+        let config = self.config;
+        let candidates: Vec<Weights> = (0..config.population).map(|_| self.sample()).collect();
+        let first_seed = config
+            .seed
+            .wrapping_add((self.generation * config.games) as u64);
+
+        let played = candidates.clone();
+        let rewards = self.envs.run(move |env, i| {
+            let bot = Bot::new(played[i / config.games]);
+            let seed = first_seed.wrapping_add((i % config.games) as u64);
+            play_game(env, &bot, seed, config.max_pieces)
+        });
+        let fitness: Vec<f32> = rewards
+            .chunks(config.games)
+            .map(|games| games.iter().sum::<f32>() / games.len() as f32)
+            .collect();
+
+        let mut ranked: Vec<usize> = (0..candidates.len()).collect();
+        ranked.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+        let elites: Vec<[f32; 8]> = ranked
+            .iter()
+            .take(config.elites.clamp(1, candidates.len()))
+            .map(|&i| candidates[i].to_array())
+            .collect();
+        self.refit(&elites);
+
+        self.generation += 1;
+        let generation = Generation {
+            number: self.generation,
+            best: candidates[ranked[0]],
+            best_fitness: fitness[ranked[0]],
+            mean_fitness: fitness.iter().sum::<f32>() / fitness.len() as f32,
+        };
+        if self
+            .best
+            .is_none_or(|(_, best)| generation.best_fitness > best)
+        {
+            self.best = Some((generation.best, generation.best_fitness));
+        }
+        generation
+    }
+
+    fn sample(&self) -> Weights {
+        let mut values = self.mean;
+        for (value, spread) in values.iter_mut().zip(self.spread) {
+            *value += spread * self.normal();
+        }
+        Weights::from_array(values)
+    }
+
+    /// A standard normal sample, by the Box-Muller transform.
+    fn normal(&self) -> f32 {
+        let u = self.rng.gen_range(f32::EPSILON, 1.0);
+        let v = self.rng.gen_range(0.0, 1.0);
+        (-2.0 * u.ln()).sqrt() * (TAU * v).cos()
+    }
+
+    fn refit(&mut self, elites: &[[f32; 8]]) {
+        let count = elites.len() as f32;
+        for k in 0..self.mean.len() {
+            let mean = elites.iter().map(|values| values[k]).sum::<f32>() / count;
+            let variance = elites
+                .iter()
+                .map(|values| (values[k] - mean).powi(2))
+                .sum::<f32>()
+                / count;
+            self.mean[k] = mean;
+            self.spread[k] = (variance + self.config.noise).sqrt();
+        }
+    }
+}
+
+/// Play a game dealt from `seed`, locking each piece wherever `bot` likes
+/// best, hold included, until it ends or `max_pieces` are down. Returns the
+/// total reward.
+pub fn play_game(env: &mut Env, bot: &Bot, seed: u64, max_pieces: u32) -> f32 {
+    env.reset(seed);
+    let mut total = 0.0;
+
+    while env.state().stats.pieces_placed < max_pieces {
+        let board = env.state().board.clone();
+        let best = env
+            .placements()
+            .iter()
+            .map(|action| bot.evaluate(&board, &action.placement))
+            .enumerate()
+            .fold(None, |best: Option<(usize, f32)>, (i, score)| match best {
+                Some((_, top)) if top >= score => best,
+                _ => Some((i, score)),
+            });
+        let Some((index, _)) = best else {
+            break;
+        };
+
+        let (_, reward, done, _) = env.step(Action::Place(index));
+        total += reward;
+        if done {
+            break;
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_config() -> TunerConfig {
+        TunerConfig {
+            population: 4,
+            elites: 2,
+            games: 2,
+            max_pieces: 30,
+            ..TunerConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_play_game_clears_lines() {
+        let mut env = Env::new();
+        let lines = play_game(&mut env, &Bot::default(), 7, 100);
+
+        assert_eq!(env.state().stats.pieces_placed, 100);
+        assert!(lines >= 30.0);
+        assert_eq!(play_game(&mut env, &Bot::default(), 7, 100), lines);
+    }
+
+    #[test]
+    fn test_tuning_is_reproducible() {
+        let mut first = Tuner::new(small_config(), Weights::default());
+        let mut second = Tuner::new(small_config(), Weights::default());
+
+        let mut top = f32::NEG_INFINITY;
+        for number in 1..=2 {
+            let generation = first.step();
+            assert_eq!(generation.number, number);
+            assert!(generation.best_fitness >= generation.mean_fitness);
+            assert_eq!(second.step(), generation);
+            top = top.max(generation.best_fitness);
+        }
+        assert_eq!(first.best().map(|(_, fitness)| fitness), Some(top));
+    }
+}
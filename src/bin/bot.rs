@@ -0,0 +1,26 @@
+//! `rust-tetris-bot`: the built-in heuristic AI as a Tetris Bot Protocol
+//! bot, reading JSON lines on stdin and answering on stdout. Pass
+//! `--weights <file>` to play with tuned weights, or `--stub` for a bot that
+//! only drops pieces straight down.
+
+use std::io;
+
+use rust_tetris::ai::{Bot, Weights};
+use rust_tetris::tbp::{StubBot, TbpServer};
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let (stdin, stdout) = (io::stdin().lock(), io::stdout().lock());
+    if args.iter().any(|arg| arg == "--stub") {
+        return TbpServer::new(StubBot).run(stdin, stdout);
+    }
+
+    let weights = match args.iter().position(|arg| arg == "--weights") {
+        Some(i) => {
+            let path = args.get(i + 1).map_or("", String::as_str);
+            Weights::load(path).map_err(|err| io::Error::other(format!("{path}: {err}")))?
+        }
+        None => Weights::default(),
+    };
+    TbpServer::new(Bot::new(weights)).run(stdin, stdout)
+}
@@ -0,0 +1,94 @@
+//! `rust-tetris-tune`: tunes the heuristic AI's weights with the
+//! cross-entropy method, playing headless seeded games on every core.
+//!
+//! Options: `--generations N`, `--population N`, `--elites N`, `--games N`,
+//! `--pieces N` (cut-off per game), `--fitness lines|score`, `--seed N`,
+//! `--weights <file>` to start from, and `--out <file>` for the best
+//! weights, rewritten after every generation.
+
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use rust_tetris::ai::{Tuner, TunerConfig, Weights};
+use rust_tetris::config::TUNER_GENERATIONS;
+use rust_tetris::env::Reward;
+
+const DEFAULT_OUT: &str = "weights.txt";
+
+/// Value following `flag` on the command line, if any.
+fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag);
+    args.next();
+    args.next()
+}
+
+/// Parsed value following `flag`, or `default` if it is missing.
+fn arg_or<T: FromStr>(flag: &str, default: T) -> Result<T, String> {
+    match arg_value(flag) {
+        Some(value) => value.parse().map_err(|_| format!("bad {flag} '{value}'")),
+        None => Ok(default),
+    }
+}
+
+fn config_from_args() -> Result<TunerConfig, String> {
+    let defaults = TunerConfig::default();
+    let fitness = match arg_value("--fitness").as_deref() {
+        None | Some("lines") => Reward::Lines,
+        Some("score") => Reward::Score,
+        Some(other) => {
+            return Err(format!(
+                "unknown fitness '{other}', expected lines or score"
+            ))
+        }
+    };
+
+    let config = TunerConfig {
+        population: arg_or("--population", defaults.population)?,
+        elites: arg_or("--elites", defaults.elites)?,
+        games: arg_or("--games", defaults.games)?,
+        max_pieces: arg_or("--pieces", defaults.max_pieces)?,
+        fitness,
+        seed: arg_or("--seed", defaults.seed)?,
+        ..defaults
+    };
+    if config.population == 0 || config.games == 0 {
+        return Err("--population and --games must be at least 1".to_owned());
+    }
+    Ok(config)
+}
+
+fn run() -> Result<(), String> {
+    let config = config_from_args()?;
+    let generations = arg_or("--generations", TUNER_GENERATIONS)?;
+    let out = arg_value("--out").unwrap_or_else(|| DEFAULT_OUT.to_owned());
+    let start = match arg_value("--weights") {
+        Some(path) => Weights::load(&path).map_err(|err| format!("{path}: {err}"))?,
+        None => Weights::default(),
+    };
+
+    let mut tuner = Tuner::new(config, start);
+    for _ in 0..generations {
+        let generation = tuner.step();
+        println!(
+            "generation {}: best {:.1}, mean {:.1}",
+            generation.number, generation.best_fitness, generation.mean_fitness
+        );
+
+        if let Some((weights, fitness)) = tuner.best() {
+            let text = format!("# fitness {fitness:.1} per game\n{weights}");
+            std::fs::write(&out, text).map_err(|err| format!("{out}: {err}"))?;
+        }
+    }
+    println!("best weights written to {out}");
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -2,26 +2,165 @@ use macroquad::prelude::Color;
 
 pub const BOARD_WIDTH: i32 = 10;
 pub const BOARD_HEIGHT: i32 = 20;
+/// Hidden rows kept above the visible board for stacks pushed up by garbage.
+pub const BUFFER_HEIGHT: i32 = 20;
 
 pub const BLOCK_SIZE: f32 = 24.0;
 pub const BOARD_OFFSET_X: f32 = 60.0;
 pub const BOARD_OFFSET_Y: f32 = 40.0;
+pub const UI_PANEL_WIDTH: f32 = 150.0;
+
+/// Default seconds a fading stack stays visible after locking.
+pub const FADE_SECONDS: f32 = 5.0;
+/// Seconds a hidden stack is outlined after a line clear.
+pub const STACK_OUTLINE_SECONDS: f32 = 0.5;
 
 pub const GB_DARK: Color = Color::new(0.06, 0.22, 0.06, 1.0);
 pub const GB_MED_DARK: Color = Color::new(0.19, 0.38, 0.19, 1.0);
 pub const GB_MED_LIGHT: Color = Color::new(0.55, 0.67, 0.06, 1.0);
 pub const GB_LIGHT: Color = Color::new(0.68, 0.89, 0.18, 1.0);
 
+/// Seconds left or right must be held before the piece slides to the wall.
+pub const DAS_SECONDS: f32 = 0.167;
+/// Seconds the correct inputs are shown after a finesse fault.
+pub const FINESSE_FLASH_SECONDS: f32 = 1.5;
+/// Targets to hit in a finesse training session.
+pub const FINESSE_TRAINER_TARGETS: u32 = 50;
+
+/// Rows the PC trainer's perfect clears fill.
+pub const PC_HEIGHT: i32 = 4;
+/// Seeds the PC trainer tries before giving up on finding a bag that
+/// solves its opener, and the positions it searches for each, which
+/// together bound how long starting a game can take.
+pub const PC_SEED_ATTEMPTS: u64 = 20;
+pub const PC_SEARCH_POSITIONS: u32 = 2_000;
+/// Positions the PC trainer searches after each lock to check a clear is
+/// still possible, kept small so the check fits in a frame. A check that
+/// runs out counts as possible.
+pub const PC_CHECK_POSITIONS: u32 = 300;
+
+/// Rate the gravity and delay frame counters run at.
+pub const FRAMES_PER_SECOND: f32 = 60.0;
 pub const INITIAL_DROP_SPEED: f32 = 0.8;
 pub const MIN_DROP_SPEED: f32 = 0.1;
 pub const SPEED_INCREASE_PER_LEVEL: f32 = 0.07;
 pub const LINES_PER_LEVEL: u32 = 10;
+pub const VARIABLE_GOAL_LINES: u32 = 5;
+
+pub const COUNTDOWN_SECONDS: f32 = 3.0;
+pub const SPRINT_LINES: u32 = 40;
+pub const SPRINT_SPLIT_LINES: u32 = 10;
 
 pub const SCORE_SINGLE: u32 = 40;
 pub const SCORE_DOUBLE: u32 = 100;
 pub const SCORE_TRIPLE: u32 = 300;
 pub const SCORE_TETRIS: u32 = 1200;
 
+pub const SCORE_TSPIN_MINI: u32 = 100;
+pub const SCORE_TSPIN_MINI_SINGLE: u32 = 200;
+pub const SCORE_TSPIN_MINI_DOUBLE: u32 = 400;
+pub const SCORE_TSPIN: u32 = 400;
+pub const SCORE_TSPIN_SINGLE: u32 = 800;
+pub const SCORE_TSPIN_DOUBLE: u32 = 1200;
+pub const SCORE_TSPIN_TRIPLE: u32 = 1600;
+pub const SCORE_COMBO: u32 = 50;
+
+pub const ULTRA_SECONDS: f32 = 120.0;
+
+pub const BTYPE_LINES: u32 = 25;
+/// Garbage rows for each B-Type "high" setting, as on the Game Boy.
+pub const BTYPE_HIGH_ROWS: [u32; 6] = [0, 3, 5, 8, 10, 12];
+pub const BTYPE_BONUS_PER_LEVEL: u32 = 1000;
+
+pub const CHEESE_ROWS: u32 = 10;
+
+pub const MARATHON_LINES: u32 = 150;
+pub const MARATHON_LONG_LINES: u32 = 200;
+pub const MARATHON_MAX_LEVEL: u32 = 15;
+/// Frames a grounded Marathon piece waits before locking, at any speed.
+pub const MARATHON_LOCK_DELAY: u32 = 30;
+
+pub const MASTER_MAX_LEVEL: u32 = 999;
+pub const MASTER_SECTION_LEVELS: u32 = 100;
+/// Gravity in 1/256ths of a row per frame from each level on, after TGM.
+/// 5120 is 20G.
+pub const MASTER_GRAVITY: &[(u32, u32)] = &[
+    (0, 4),
+    (30, 6),
+    (35, 8),
+    (40, 10),
+    (50, 12),
+    (60, 16),
+    (70, 32),
+    (80, 48),
+    (90, 64),
+    (100, 80),
+    (120, 96),
+    (140, 112),
+    (160, 128),
+    (170, 144),
+    (200, 4),
+    (220, 32),
+    (230, 64),
+    (233, 96),
+    (236, 128),
+    (239, 160),
+    (243, 192),
+    (247, 224),
+    (251, 256),
+    (300, 512),
+    (330, 768),
+    (360, 1024),
+    (400, 1280),
+    (420, 1024),
+    (450, 768),
+    (500, 5120),
+];
+/// ARE, lock delay and line clear delay in frames from each level on.
+pub const MASTER_DELAYS: &[(u32, u32, u32, u32)] = &[
+    (0, 25, 30, 40),
+    (500, 25, 30, 25),
+    (600, 16, 30, 16),
+    (700, 12, 30, 12),
+    (800, 6, 30, 6),
+    (900, 6, 17, 6),
+];
+/// Grades from lowest to highest. Grand Master is awarded on top of these.
+pub const MASTER_GRADES: [&str; 18] = [
+    "9", "8", "7", "6", "5", "4", "3", "2", "1", "S1", "S2", "S3", "S4", "S5", "S6", "S7", "S8",
+    "S9",
+];
+/// Grade points for a single, double, triple and tetris, in three tiers
+/// that get stingier as the grade rises.
+pub const MASTER_GRADE_POINTS: [[u32; 4]; 3] = [[10, 20, 40, 50], [5, 15, 30, 40], [2, 12, 25, 30]];
+pub const MASTER_POINTS_PER_GRADE: u32 = 100;
+/// Seconds for one grade point to decay at the lowest grade; higher
+/// grades decay faster.
+pub const MASTER_DECAY_SECONDS: f32 = 2.0;
+/// Reaching level 999 within this time at the top grade earns Grand Master.
+pub const MASTER_GM_SECONDS: f32 = 810.0;
+
+/// Pieces per second the autoplay demo places by default.
+pub const AUTOPLAY_PPS: f32 = 2.0;
+/// Seconds the autoplay demo shows the results before starting again.
+pub const AUTOPLAY_RESTART_SECONDS: f32 = 3.0;
+/// Seconds an external TBP bot gets to finish the handshake or suggest a
+/// move before the game hands back to the keyboard.
+pub const TBP_TIMEOUT_SECONDS: f32 = 5.0;
+
+/// Defaults for the weight tuner: generations run, weight sets per
+/// generation, how many of the best the next is drawn around, games each
+/// set plays and the piece count a game is cut off at.
+pub const TUNER_GENERATIONS: usize = 20;
+pub const TUNER_POPULATION: usize = 50;
+pub const TUNER_ELITES: usize = 10;
+pub const TUNER_GAMES: usize = 8;
+pub const TUNER_MAX_PIECES: u32 = 500;
+/// Starting standard deviation of each weight around the initial ones.
+pub const TUNER_SPREAD: f32 = 2.0;
+/// Variance added back to each weight every generation.
+pub const TUNER_NOISE: f32 = 0.25;
+
 pub const WINDOW_TITLE: &str = "Game Boy Tetris";
 pub const WINDOW_WIDTH: i32 = 480;
 pub const WINDOW_HEIGHT: i32 = 640;
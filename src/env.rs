@@ -0,0 +1,505 @@
+//! A gym-style environment for training agents on headless games.
+//!
+//! `Env` wraps one `GameState`. Agents either pick whole placements from
+//! `Env::placements` or press buttons frame by frame. `VecEnv` steps many
+//! environments at once on a pool of threads.
+
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::ai::{find_placements, Input, Placement};
+use crate::config::FRAMES_PER_SECOND;
+use crate::game::mode::Endless;
+use crate::game::{GameMode, GameState, Outcome, Ruleset};
+use crate::tetromino::{Tetromino, TetrominoType};
+
+/// One button an agent can press on a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    RotateCw,
+    RotateCcw,
+    /// Move down one row.
+    SoftDrop,
+    HardDrop,
+    Hold,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Lock the piece at `Env::placements()[index]`. The moves take no
+    /// time, but the step then runs frames through the delay before the
+    /// next piece, which count toward elapsed time and timed goals.
+    Place(usize),
+    /// Run one frame, pressing a button first if given.
+    Frame(Option<Button>),
+}
+
+/// What each step is rewarded with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Reward {
+    /// Lines cleared.
+    #[default]
+    Lines,
+    /// Points scored.
+    Score,
+}
+
+/// A placement the agent can choose, and whether it plays the piece out
+/// of hold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacementAction {
+    pub placement: Placement,
+    pub hold: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub width: usize,
+    pub height: usize,
+    /// Visible cells row by row from the top, 1 where filled.
+    pub board: Vec<u8>,
+    /// The falling piece and where it is.
+    pub current: Tetromino,
+    pub next: TetrominoType,
+    pub hold: Option<TetrominoType>,
+    pub can_hold: bool,
+    pub score: u32,
+    pub lines: u32,
+    pub level: u32,
+    pub pieces: u32,
+    pub elapsed: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StepInfo {
+    pub lines_cleared: u32,
+    pub pieces_placed: u32,
+    /// How the game ended, once it has.
+    pub outcome: Option<Outcome>,
+    /// The action named a placement that does not exist and was ignored.
+    pub invalid_action: bool,
+}
+
+/// What `step` returns: the new observation, the reward, whether the game
+/// is over, and extra information.
+pub type Step = (Observation, f32, bool, StepInfo);
+
+type ModeFactory = Box<dyn Fn() -> Box<dyn GameMode> + Send + Sync>;
+
+pub struct Env {
+    state: GameState,
+    ruleset: Ruleset,
+    make_mode: ModeFactory,
+    pub reward: Reward,
+    placements: Option<Vec<PlacementAction>>,
+}
+
+impl Env {
+    /// Endless play on the standard board.
+    pub fn new() -> Self {
+        Self::with_mode(Ruleset::standard(), || Box::new(Endless))
+    }
+
+    /// Games of the mode `make_mode` builds, which is called on every reset.
+    pub fn with_mode(
+        ruleset: Ruleset,
+        make_mode: impl Fn() -> Box<dyn GameMode> + Send + Sync + 'static,
+    ) -> Self {
+        Env {
+            state: GameState::with_seed(ruleset, make_mode(), 0),
+            ruleset,
+            make_mode: Box::new(make_mode),
+            reward: Reward::default(),
+            placements: None,
+        }
+    }
+
+    pub fn with_reward(mut self, reward: Reward) -> Self {
+        self.reward = reward;
+        self
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Start a new game whose pieces are dealt from `seed`, skipping any
+    /// countdown.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.state = GameState::with_seed(self.ruleset, (self.make_mode)(), seed);
+        self.state.update(self.state.countdown());
+        self.placements = None;
+        self.observe()
+    }
+
+    /// Every placement `Action::Place` can choose from right now: those of
+    /// the current piece, then those of the piece hold would bring out.
+    pub fn placements(&mut self) -> &[PlacementAction] {
+        let state = &self.state;
+        self.placements.get_or_insert_with(|| {
+            if state.game_over {
+                return Vec::new();
+            }
+            let mut actions: Vec<_> = find_placements(&state.board, &state.current_piece)
+                .into_iter()
+                .map(|placement| PlacementAction {
+                    placement,
+                    hold: false,
+                })
+                .collect();
+            if state.can_hold() {
+                let held = state.hold_piece.unwrap_or(state.next_piece.piece_type);
+                let piece = Tetromino::spawn(held, state.board.width());
+                actions.extend(find_placements(&state.board, &piece).into_iter().map(
+                    |placement| PlacementAction {
+                        placement,
+                        hold: true,
+                    },
+                ));
+            }
+            actions
+        })
+    }
+
+    pub fn step(&mut self, action: Action) -> Step {
+        let (lines, score, pieces) = (
+            self.state.scoring.lines_cleared,
+            self.state.scoring.score,
+            self.state.stats.pieces_placed,
+        );
+        let mut info = StepInfo::default();
+
+        if !self.state.game_over {
+            match action {
+                Action::Place(index) => match self.placements().get(index).cloned() {
+                    Some(action) => self.place(action),
+                    None => info.invalid_action = true,
+                },
+                Action::Frame(button) => self.frame(button),
+            }
+            self.placements = None;
+        }
+
+        info.lines_cleared = self.state.scoring.lines_cleared - lines;
+        info.pieces_placed = self.state.stats.pieces_placed - pieces;
+        info.outcome = self.state.outcome;
+        let reward = match self.reward {
+            Reward::Lines => info.lines_cleared as f32,
+            Reward::Score => (self.state.scoring.score - score) as f32,
+        };
+        (self.observe(), reward, self.state.game_over, info)
+    }
+
+    fn place(&mut self, action: PlacementAction) {
+        if action.hold {
+            self.state.hold();
+        }
+        for input in action.placement.inputs {
+            self.state.apply_input(input);
+        }
+        while self.state.is_entry_delay() && !self.state.game_over {
+            self.state.update(1.0 / FRAMES_PER_SECOND);
+        }
+    }
+
+    fn frame(&mut self, button: Option<Button>) {
+        if !self.state.is_entry_delay() {
+            match button {
+                Some(Button::Left) => _ = self.state.apply_input(Input::Left),
+                Some(Button::Right) => _ = self.state.apply_input(Input::Right),
+                Some(Button::RotateCw) => _ = self.state.apply_input(Input::RotateCw),
+                Some(Button::RotateCcw) => _ = self.state.apply_input(Input::RotateCcw),
+                Some(Button::SoftDrop) => _ = self.state.try_move(0, 1),
+                Some(Button::HardDrop) => self.state.hard_drop(),
+                Some(Button::Hold) => _ = self.state.hold(),
+                None => {}
+            }
+        }
+        self.state.update(1.0 / FRAMES_PER_SECOND);
+    }
+
+    pub fn observe(&self) -> Observation {
+        let state = &self.state;
+        let (width, height) = (state.board.width(), state.board.height());
+        let board = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| state.board.has_block(x, y) as u8)
+            .collect();
+
+        Observation {
+            width: width as usize,
+            height: height as usize,
+            board,
+            current: state.current_piece,
+            next: state.next_piece.piece_type,
+            hold: state.hold_piece,
+            can_hold: state.can_hold(),
+            score: state.scoring.score,
+            lines: state.scoring.lines_cleared,
+            level: state.scoring.level,
+            pieces: state.stats.pieces_placed,
+            elapsed: state.stats.elapsed,
+        }
+    }
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Work for one worker: a closure over its environments and the index of
+/// the first of them.
+type Job = Box<dyn FnOnce(&mut [Env], usize) + Send>;
+
+struct Worker {
+    jobs: Sender<Job>,
+    thread: JoinHandle<()>,
+}
+
+/// Many environments stepped together on a pool of worker threads, one per
+/// core, each owning its share of the environments for as long as the
+/// batch lives. Finished games stay finished until they are reset.
+pub struct VecEnv {
+    workers: Vec<Worker>,
+    len: usize,
+}
+
+impl VecEnv {
+    pub fn new(count: usize, make_env: impl Fn() -> Env) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = count.div_ceil(threads).max(1);
+
+        let mut envs: Vec<Env> = (0..count).map(|_| make_env()).collect();
+        let mut workers = Vec::new();
+        let mut first = 0;
+        while !envs.is_empty() {
+            let rest = envs.split_off(chunk.min(envs.len()));
+            let mut own = std::mem::replace(&mut envs, rest);
+            let (jobs, received) = mpsc::channel::<Job>();
+            let start = first;
+            first += own.len();
+            let thread = thread::spawn(move || {
+                for job in received {
+                    job(&mut own, start);
+                }
+            });
+            workers.push(Worker { jobs, thread });
+        }
+
+        VecEnv {
+            workers,
+            len: count,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Reset every environment, each with its own seed.
+    pub fn reset(&mut self, seeds: &[u64]) -> Vec<Observation> {
+        assert_eq!(seeds.len(), self.len, "one seed per environment");
+        let seeds = seeds.to_vec();
+        self.run(move |env, i| env.reset(seeds[i]))
+    }
+
+    /// Step every environment with its own action.
+    pub fn step(&mut self, actions: &[Action]) -> Vec<Step> {
+        assert_eq!(actions.len(), self.len, "one action per environment");
+        let actions = actions.to_vec();
+        self.run(move |env, i| env.step(actions[i]))
+    }
+
+    /// Apply `f` to every environment and its index on the workers, for
+    /// agents that want to drive whole games there. Results come back in
+    /// environment order.
+    pub fn run<T: Send + 'static>(
+        &mut self,
+        f: impl Fn(&mut Env, usize) -> T + Send + Sync + 'static,
+    ) -> Vec<T> {
+        let f = Arc::new(f);
+        let (results, received) = mpsc::channel();
+        for (w, worker) in self.workers.iter().enumerate() {
+            let (f, results) = (Arc::clone(&f), results.clone());
+            let job: Job = Box::new(move |envs, first| {
+                let chunk: Vec<T> = envs
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(i, env)| f(env, first + i))
+                    .collect();
+                _ = results.send((w, chunk));
+            });
+            worker.jobs.send(job).expect("environment thread panicked");
+        }
+        drop(results);
+
+        // A worker that panics drops its sender without sending.
+        let mut chunks: Vec<(usize, Vec<T>)> = received.iter().collect();
+        assert_eq!(
+            chunks.len(),
+            self.workers.len(),
+            "environment thread panicked"
+        );
+        chunks.sort_by_key(|&(w, _)| w);
+        chunks.into_iter().flat_map(|(_, chunk)| chunk).collect()
+    }
+}
+
+impl Drop for VecEnv {
+    fn drop(&mut self) {
+        for Worker { jobs, thread } in self.workers.drain(..) {
+            drop(jobs);
+            _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::Bot;
+    use crate::game::mode::Sprint;
+
+    /// Index of the placement the built-in bot would choose.
+    fn bot_action(env: &mut Env) -> Action {
+        let bot = Bot::default();
+        let board = env.state().board.clone();
+        let placements = env.placements();
+        let best = placements
+            .iter()
+            .filter(|action| !action.hold)
+            .enumerate()
+            .max_by(|a, b| {
+                bot.evaluate(&board, &a.1.placement)
+                    .total_cmp(&bot.evaluate(&board, &b.1.placement))
+            })
+            .map_or(0, |(index, _)| index);
+        Action::Place(best)
+    }
+
+    #[test]
+    fn test_reset_is_reproducible() {
+        let mut env = Env::new();
+        let first = env.reset(11);
+        env.step(Action::Place(0));
+        assert_eq!(env.reset(11), first);
+        assert_eq!(first.board.len(), 200);
+        assert_eq!(first.pieces, 0);
+    }
+
+    #[test]
+    fn test_placement_steps_clear_lines_for_reward() {
+        let mut env = Env::new();
+        env.reset(5);
+
+        let mut total = 0.0;
+        for _ in 0..60 {
+            let action = bot_action(&mut env);
+            let (observation, reward, done, info) = env.step(action);
+            assert!(!done);
+            assert_eq!(info.pieces_placed, 1);
+            assert_eq!(observation.lines, env.state().scoring.lines_cleared);
+            total += reward;
+        }
+        assert!(total >= 10.0);
+        assert_eq!(total as u32, env.state().scoring.lines_cleared);
+    }
+
+    #[test]
+    fn test_stacking_in_place_tops_out() {
+        let mut env = Env::new();
+        env.reset(0);
+
+        for _ in 0..200 {
+            let (_, _, done, info) = env.step(Action::Place(0));
+            assert!(!info.invalid_action);
+            if done {
+                assert_eq!(info.outcome, Some(Outcome::ToppedOut));
+                assert!(env.placements().is_empty());
+                return;
+            }
+        }
+        panic!("the game never ended");
+    }
+
+    #[test]
+    fn test_hold_placements() {
+        let mut env = Env::new();
+        let observation = env.reset(1);
+        let held = env
+            .placements()
+            .iter()
+            .position(|action| action.hold)
+            .expect("hold is available");
+
+        let (observation_after, _, _, _) = env.step(Action::Place(held));
+        assert_eq!(observation_after.hold, Some(observation.current.piece_type));
+    }
+
+    #[test]
+    fn test_invalid_placement_is_ignored() {
+        let mut env = Env::new();
+        let before = env.reset(2);
+        let (after, reward, done, info) = env.step(Action::Place(usize::MAX));
+        assert!(info.invalid_action);
+        assert_eq!((after, reward, done), (before, 0.0, false));
+    }
+
+    #[test]
+    fn test_frame_steps_move_and_fall() {
+        let mut env = Env::with_mode(Ruleset::standard(), || Box::new(Sprint::new()));
+        let start = env.reset(3);
+        assert_eq!(env.state().countdown(), 0.0);
+
+        let (moved, _, _, _) = env.step(Action::Frame(Some(Button::Left)));
+        assert_eq!(moved.current.x, start.current.x - 1);
+
+        let mut observation = moved;
+        for _ in 0..FRAMES_PER_SECOND as usize {
+            observation = env.step(Action::Frame(None)).0;
+        }
+        assert!(observation.current.y > start.current.y);
+
+        let (_, _, _, info) = env.step(Action::Frame(Some(Button::HardDrop)));
+        assert_eq!(info.pieces_placed, 1);
+    }
+
+    #[test]
+    fn test_vec_env_matches_single_envs() {
+        let seeds: Vec<u64> = (0..8).collect();
+        let mut batch = VecEnv::new(seeds.len(), Env::new);
+        batch.reset(&seeds);
+        let results = batch.step(&vec![Action::Place(3); seeds.len()]);
+
+        for (&seed, (observation, reward, done, _)) in seeds.iter().zip(results) {
+            let mut env = Env::new();
+            env.reset(seed);
+            let expected = env.step(Action::Place(3));
+            assert_eq!(
+                (observation, reward, done),
+                (expected.0, expected.1, expected.2)
+            );
+        }
+    }
+
+    #[test]
+    fn test_vec_env_keeps_games_between_calls() {
+        let mut batch = VecEnv::new(5, Env::new);
+        batch.reset(&[0; 5]);
+        for _ in 0..3 {
+            batch.step(&[Action::Place(0); 5]);
+        }
+
+        let pieces = batch.run(|env, i| (i, env.state().stats.pieces_placed));
+        assert_eq!(pieces, (0..5).map(|i| (i, 3)).collect::<Vec<_>>());
+    }
+}
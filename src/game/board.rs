@@ -1,86 +1,325 @@
-use crate::config::{BOARD_HEIGHT, BOARD_WIDTH};
-use crate::tetromino::Tetromino;
+use super::ruleset::Ruleset;
+use super::scoring::Spin;
+use crate::config::BUFFER_HEIGHT;
+use crate::tetromino::{Tetromino, TetrominoType};
 
-#[derive(Clone, Copy, PartialEq)]
-pub struct Block {
-    pub filled: bool,
+/// A locked cell on the board, remembering where it came from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Block {
+    Piece(TetrominoType),
+    Garbage,
 }
 
+/// The playfield, stored as one occupancy bitmask per row (bit `x` set means
+/// column `x` is filled) alongside the block kind of every cell. Collision
+/// and line clears only touch the masks, and nothing allocates after `new`.
+///
+/// Rows `0..height` are visible. Above them sit `BUFFER_HEIGHT` hidden rows
+/// (negative `y`) that catch blocks pushed up by garbage.
+#[derive(Clone)]
 pub struct Board {
-    grid: Vec<Vec<Option<Block>>>,
+    width: i32,
+    height: i32,
+    full_row: u32,
+    rows: Vec<u32>,
+    cells: Vec<Option<Block>>,
+    /// When each cell's block was locked, in game seconds; moves with it.
+    lock_times: Vec<f32>,
 }
 
 impl Board {
+    /// An empty board with the standard dimensions.
     pub fn new() -> Self {
+        Self::with_ruleset(&Ruleset::standard())
+    }
+
+    pub fn with_ruleset(ruleset: &Ruleset) -> Self {
+        let (width, height) = (ruleset.board_width, ruleset.board_height);
         Board {
-            grid: vec![vec![None; BOARD_WIDTH as usize]; BOARD_HEIGHT as usize],
+            width,
+            height,
+            full_row: u32::MAX >> (32 - width),
+            rows: vec![0; (BUFFER_HEIGHT + height) as usize],
+            cells: vec![None; (width * (BUFFER_HEIGHT + height)) as usize],
+            lock_times: vec![0.0; (width * (BUFFER_HEIGHT + height)) as usize],
         }
     }
 
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
     pub fn is_valid_position(&self, piece: &Tetromino) -> bool {
         for (x, y) in piece.get_blocks() {
             // Check boundaries
-            if x < 0 || x >= BOARD_WIDTH || y >= BOARD_HEIGHT {
+            if !(0..self.width).contains(&x) || y >= self.height {
                 return false;
             }
 
             // Check collision with placed blocks
-            if y >= 0 && self.grid[y as usize][x as usize].is_some() {
+            if y >= -BUFFER_HEIGHT && self.rows[self.row_index(y)] & (1 << x) != 0 {
                 return false;
             }
         }
         true
     }
 
+    /// Rotate `piece` a quarter turn using the SRS kick tables, returning
+    /// the first kicked position that fits and the index of that kick.
+    pub fn try_rotate(&self, piece: &Tetromino, clockwise: bool) -> Option<(Tetromino, usize)> {
+        let mut rotated = *piece;
+        if clockwise {
+            rotated.rotate();
+        } else {
+            rotated.rotate_ccw();
+        }
+
+        let kicks = rotated.piece_type.kicks(piece.rotation, rotated.rotation);
+        kicks.iter().enumerate().find_map(|(index, &(dx, dy))| {
+            let mut kicked = rotated;
+            kicked.move_by(dx as i32, dy as i32);
+            self.is_valid_position(&kicked).then_some((kicked, index))
+        })
+    }
+
+    /// Three-corner T-spin check for a piece about to lock, given the kick
+    /// its last rotation used (`None` if it last moved rather than rotated).
+    /// Both corners on the pointing side make a full spin, as does the last
+    /// SRS kick.
+    pub fn detect_spin(&self, piece: &Tetromino, last_kick: Option<usize>) -> Spin {
+        let Some(kick) = last_kick else {
+            return Spin::None;
+        };
+        if piece.piece_type != TetrominoType::T {
+            return Spin::None;
+        }
+
+        // Corners of the 3x3 box, clockwise from the top left, so the two
+        // corners on the pointing side are `rotation` and the one after it.
+        let corners = [(0, 0), (2, 0), (2, 2), (0, 2)].map(|(dx, dy)| {
+            let (x, y) = (piece.x + dx, piece.y + dy);
+            !(0..self.width).contains(&x) || y >= self.height || self.has_block(x, y)
+        });
+        if corners.iter().filter(|&&filled| filled).count() < 3 {
+            return Spin::None;
+        }
+
+        let front = piece.rotation as usize;
+        if (corners[front] && corners[(front + 1) % 4]) || kick == 4 {
+            Spin::Full
+        } else {
+            Spin::Mini
+        }
+    }
+
     pub fn place_piece(&mut self, piece: &Tetromino) {
+        self.place_piece_at(piece, 0.0);
+    }
+
+    /// Lock a piece, stamping its cells with the game time `time`.
+    pub fn place_piece_at(&mut self, piece: &Tetromino, time: f32) {
         for (x, y) in piece.get_blocks() {
-            if y >= 0 && y < BOARD_HEIGHT && x >= 0 && x < BOARD_WIDTH {
-                self.grid[y as usize][x as usize] = Some(Block { filled: true });
+            if self.in_bounds(x, y) {
+                self.set_cell(x, y, Some(Block::Piece(piece.piece_type)));
+                let index = self.cell_index(x, y);
+                self.lock_times[index] = time;
             }
         }
     }
 
+    /// Remove every full row, shifting the rows above it down in place.
     pub fn clear_lines(&mut self) -> u32 {
-        let mut lines_to_clear = Vec::new();
+        let width = self.width as usize;
+        let mut cleared = 0;
+        let mut write = self.rows.len();
+
+        for read in (0..self.rows.len()).rev() {
+            if self.rows[read] == self.full_row {
+                cleared += 1;
+                continue;
+            }
 
-        for y in 0..BOARD_HEIGHT {
-            if self.is_line_full(y as usize) {
-                lines_to_clear.push(y as usize);
+            write -= 1;
+            if write != read {
+                self.rows[write] = self.rows[read];
+                self.cells
+                    .copy_within(read * width..(read + 1) * width, write * width);
+                self.lock_times
+                    .copy_within(read * width..(read + 1) * width, write * width);
             }
         }
 
-        for &line in lines_to_clear.iter().rev() {
-            self.grid.remove(line);
-            self.grid.insert(0, vec![None; BOARD_WIDTH as usize]);
+        self.rows[..write].fill(0);
+        self.cells[..write * width].fill(None);
+        self.lock_times[..write * width].fill(0.0);
+
+        cleared
+    }
+
+    /// Push `rows` garbage rows up from the bottom, each filled except for
+    /// `hole_column`. Returns `false` if this pushed blocks out of the top
+    /// of the buffer zone, which tops the player out.
+    pub fn add_garbage(&mut self, rows: u32, hole_column: i32) -> bool {
+        self.add_garbage_with(rows, |_| hole_column)
+    }
+
+    /// Like `add_garbage`, but asks `hole_for_row` for the hole column of
+    /// each new row, counting up from the bottom one.
+    pub fn add_garbage_with(
+        &mut self,
+        rows: u32,
+        mut hole_for_row: impl FnMut(u32) -> i32,
+    ) -> bool {
+        let full_row = self.full_row;
+        let last_column = self.width - 1;
+        self.add_garbage_rows(rows, |row| {
+            full_row & !(1 << hole_for_row(row).clamp(0, last_column))
+        })
+    }
+
+    /// Push `rows` garbage rows up from the bottom, asking `mask_for_row`
+    /// which columns of each new row are filled (bit `x` for column `x`),
+    /// counting up from the bottom one. Callers should leave at least one
+    /// hole per row. Returns `false` on a top out, like `add_garbage`.
+    pub fn add_garbage_rows(
+        &mut self,
+        rows: u32,
+        mut mask_for_row: impl FnMut(u32) -> u32,
+    ) -> bool {
+        let total = self.rows.len();
+        let shift = (rows as usize).min(total);
+        let width = self.width as usize;
+        let fits = self.rows[..shift].iter().all(|&row| row == 0);
+
+        self.rows.copy_within(shift.., 0);
+        self.cells.copy_within(shift * width.., 0);
+        self.lock_times.copy_within(shift * width.., 0);
+        self.lock_times[(total - shift) * width..].fill(0.0);
+
+        for i in 0..shift {
+            let index = total - 1 - i;
+            let mask = mask_for_row(i as u32) & self.full_row;
+            self.rows[index] = mask;
+
+            let row_cells = &mut self.cells[index * width..(index + 1) * width];
+            for (x, cell) in row_cells.iter_mut().enumerate() {
+                *cell = (mask & (1 << x) != 0).then_some(Block::Garbage);
+            }
         }
 
-        lines_to_clear.len() as u32
+        fits
+    }
+
+    /// Number of rows, buffer included, that still hold garbage cells.
+    pub fn garbage_rows(&self) -> u32 {
+        self.cells
+            .chunks(self.width as usize)
+            .filter(|row| row.contains(&Some(Block::Garbage)))
+            .count() as u32
+    }
+
+    /// Occupancy mask of a completely filled row.
+    pub fn full_row_mask(&self) -> u32 {
+        self.full_row
     }
 
-    fn is_line_full(&self, y: usize) -> bool {
-        self.grid[y].iter().all(|block| block.is_some())
+    /// Game time the block at `(x, y)` was locked by `place_piece_at`;
+    /// zero for empty cells and blocks set any other way.
+    pub fn locked_at(&self, x: i32, y: i32) -> f32 {
+        if self.in_bounds(x, y) {
+            self.lock_times[self.cell_index(x, y)]
+        } else {
+            0.0
+        }
     }
 
     pub fn get_block(&self, x: i32, y: i32) -> Option<Block> {
-        if x >= 0 && x < BOARD_WIDTH && y >= 0 && y < BOARD_HEIGHT {
-            self.grid[y as usize][x as usize]
+        if self.in_bounds(x, y) {
+            self.cells[self.cell_index(x, y)]
         } else {
             None
         }
     }
 
+    /// Set or clear a single cell; positions off the board are ignored.
+    pub fn set_block(&mut self, x: i32, y: i32, block: Option<Block>) {
+        if self.in_bounds(x, y) {
+            self.set_cell(x, y, block);
+        }
+    }
+
     pub fn has_block(&self, x: i32, y: i32) -> bool {
-        self.get_block(x, y).is_some()
+        self.in_bounds(x, y) && self.rows[self.row_index(y)] & (1 << x) != 0
+    }
+
+    /// Occupancy bitmask of every visible row, top row first.
+    pub fn rows(&self) -> &[u32] {
+        &self.rows[BUFFER_HEIGHT as usize..]
+    }
+
+    /// Occupancy bitmask of row `y`, buffer rows included; rows above the
+    /// buffer are empty.
+    pub fn row(&self, y: i32) -> u32 {
+        if (-BUFFER_HEIGHT..self.height).contains(&y) {
+            self.rows[self.row_index(y)]
+        } else {
+            0
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn grid(&self) -> &Vec<Vec<Option<Block>>> {
-        &self.grid
+    /// Whether no block is left anywhere, buffer included.
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+
+    /// Whether any locked block sits in the hidden rows above the board.
+    pub fn is_buffer_occupied(&self) -> bool {
+        self.rows[..BUFFER_HEIGHT as usize]
+            .iter()
+            .any(|&row| row != 0)
     }
 
-    #[allow(dead_code)]
     pub fn clear(&mut self) {
-        self.grid = vec![vec![None; BOARD_WIDTH as usize]; BOARD_HEIGHT as usize];
+        self.rows.fill(0);
+        self.cells.fill(None);
+        self.lock_times.fill(0.0);
+    }
+
+    /// Empty the buffer zone and every visible row above `y`, leaving the
+    /// rest of the stack where it is.
+    pub fn clear_above(&mut self, y: i32) {
+        let rows = self.row_index(y.clamp(-BUFFER_HEIGHT, self.height));
+        self.rows[..rows].fill(0);
+        self.cells[..rows * self.width as usize].fill(None);
+        self.lock_times[..rows * self.width as usize].fill(0.0);
+    }
+
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (-BUFFER_HEIGHT..self.height).contains(&y)
+    }
+
+    fn row_index(&self, y: i32) -> usize {
+        (y + BUFFER_HEIGHT) as usize
+    }
+
+    fn cell_index(&self, x: i32, y: i32) -> usize {
+        self.row_index(y) * self.width as usize + x as usize
+    }
+
+    fn set_cell(&mut self, x: i32, y: i32, block: Option<Block>) {
+        let bit = 1 << x;
+        let row = self.row_index(y);
+        match block {
+            Some(_) => self.rows[row] |= bit,
+            None => self.rows[row] &= !bit,
+        }
+        let index = self.cell_index(x, y);
+        self.cells[index] = block;
+        self.lock_times[index] = 0.0;
     }
 }
 
@@ -93,14 +332,16 @@ impl Default for Board {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tetromino::types::TetrominoType;
-    use crate::tetromino::piece::Tetromino;
+    use crate::config::{BOARD_HEIGHT, BOARD_WIDTH};
 
     #[test]
     fn test_board_creation() {
         let board = Board::new();
-        assert_eq!(board.grid.len(), BOARD_HEIGHT as usize);
-        assert_eq!(board.grid[0].len(), BOARD_WIDTH as usize);
+        assert_eq!(board.rows().len(), BOARD_HEIGHT as usize);
+        assert_eq!(
+            board.cells.len(),
+            (BOARD_WIDTH * (BOARD_HEIGHT + BUFFER_HEIGHT)) as usize
+        );
     }
 
     #[test]
@@ -117,19 +358,218 @@ mod tests {
         piece.y = BOARD_HEIGHT - 2;
 
         board.place_piece(&piece);
-        assert!(board.has_block(piece.x, piece.y));
+        let (x, y) = piece.get_blocks()[0];
+        assert!(board.has_block(x, y));
+        assert_eq!(board.get_block(x, y), Some(Block::Piece(TetrominoType::O)));
     }
 
     #[test]
     fn test_line_clearing() {
         let mut board = Board::new();
-        
+
         for x in 0..BOARD_WIDTH {
-            board.grid[(BOARD_HEIGHT - 1) as usize][x as usize] = Some(Block { filled: true });
+            board.set_cell(x, BOARD_HEIGHT - 1, Some(Block::Garbage));
         }
 
         let cleared = board.clear_lines();
         assert_eq!(cleared, 1);
         assert!(!board.has_block(0, BOARD_HEIGHT - 1));
     }
+
+    #[test]
+    fn test_clear_lines_shifts_cells_with_masks() {
+        let mut board = Board::new();
+        let bottom = BOARD_HEIGHT - 1;
+
+        for x in 0..BOARD_WIDTH {
+            board.set_cell(x, bottom, Some(Block::Garbage));
+            board.set_cell(x, bottom - 2, Some(Block::Garbage));
+        }
+        board.set_cell(3, bottom - 1, Some(Block::Piece(TetrominoType::T)));
+        board.set_cell(5, bottom - 3, Some(Block::Piece(TetrominoType::L)));
+
+        assert_eq!(board.clear_lines(), 2);
+        assert_eq!(
+            board.get_block(3, bottom),
+            Some(Block::Piece(TetrominoType::T))
+        );
+        assert_eq!(
+            board.get_block(5, bottom - 1),
+            Some(Block::Piece(TetrominoType::L))
+        );
+        assert_eq!(board.rows()[bottom as usize], 1 << 3);
+        assert_eq!(board.rows()[(bottom - 1) as usize], 1 << 5);
+        assert!(board.rows()[..(bottom - 1) as usize]
+            .iter()
+            .all(|&row| row == 0));
+    }
+
+    #[test]
+    fn test_custom_dimensions() {
+        let mut board = Board::with_ruleset(&Ruleset::four_wide());
+        assert_eq!(board.width(), 4);
+
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.x = 0;
+        piece.y = board.height() - 1;
+        assert!(board.is_valid_position(&piece));
+
+        board.place_piece(&piece);
+        assert_eq!(board.clear_lines(), 1);
+        assert!(board.rows().iter().all(|&row| row == 0));
+    }
+
+    #[test]
+    fn test_big_board_uses_full_width() {
+        let mut board = Board::with_ruleset(&Ruleset::big());
+        let bottom = board.height() - 1;
+
+        for x in 0..board.width() {
+            board.set_cell(x, bottom, Some(Block::Garbage));
+        }
+        assert!(!board.has_block(20, bottom));
+        assert_eq!(board.clear_lines(), 1);
+    }
+
+    #[test]
+    fn test_add_garbage_raises_stack() {
+        let mut board = Board::new();
+        let bottom = BOARD_HEIGHT - 1;
+        board.set_cell(2, bottom, Some(Block::Piece(TetrominoType::J)));
+
+        assert!(board.add_garbage(2, 4));
+        assert_eq!(
+            board.get_block(2, bottom - 2),
+            Some(Block::Piece(TetrominoType::J))
+        );
+        for y in [bottom - 1, bottom] {
+            assert_eq!(board.get_block(0, y), Some(Block::Garbage));
+            assert!(!board.has_block(4, y));
+        }
+        assert_eq!(board.clear_lines(), 0);
+    }
+
+    #[test]
+    fn test_add_garbage_with_per_row_holes() {
+        let mut board = Board::new();
+        let bottom = BOARD_HEIGHT - 1;
+
+        assert!(board.add_garbage_with(3, |row| row as i32));
+        assert!(!board.has_block(0, bottom));
+        assert!(!board.has_block(1, bottom - 1));
+        assert!(!board.has_block(2, bottom - 2));
+        assert!(board.has_block(0, bottom - 2));
+    }
+
+    #[test]
+    fn test_garbage_pushes_stack_into_buffer() {
+        let mut board = Board::new();
+        board.set_cell(5, 0, Some(Block::Piece(TetrominoType::I)));
+
+        assert!(board.add_garbage(1, 0));
+        assert!(board.is_buffer_occupied());
+        assert!(board.has_block(5, -1));
+
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.rotate();
+        piece.x = 3;
+        piece.y = -2;
+        assert!(!board.is_valid_position(&piece));
+    }
+
+    #[test]
+    fn test_garbage_past_buffer_tops_out() {
+        let mut board = Board::new();
+        board.set_cell(5, -BUFFER_HEIGHT, Some(Block::Piece(TetrominoType::I)));
+
+        assert!(!board.add_garbage(1, 0));
+    }
+
+    #[test]
+    fn test_add_garbage_rows_uses_masks() {
+        let mut board = Board::with_ruleset(&Ruleset::six_wide());
+        let bottom = board.height() - 1;
+
+        assert!(board.add_garbage_rows(2, |row| if row == 0 { 0b101010 } else { 0b000011 }));
+        assert_eq!(board.rows()[bottom as usize], 0b101010);
+        assert_eq!(board.rows()[(bottom - 1) as usize], 0b000011);
+        assert_eq!(board.get_block(1, bottom), Some(Block::Garbage));
+        assert_eq!(board.get_block(0, bottom), None);
+    }
+
+    #[test]
+    fn test_garbage_rows_counts_tagged_rows() {
+        let mut board = Board::new();
+        assert!(board.add_garbage(3, 0));
+        board.set_block(0, BOARD_HEIGHT - 1, Some(Block::Piece(TetrominoType::I)));
+        assert_eq!(board.garbage_rows(), 3);
+
+        for x in 1..BOARD_WIDTH {
+            board.set_block(x, BOARD_HEIGHT - 1, None);
+        }
+        assert_eq!(board.garbage_rows(), 2);
+    }
+
+    #[test]
+    fn test_clear_above() {
+        let mut board = Board::new();
+        assert!(board.add_garbage(BOARD_HEIGHT as u32, 0));
+        board.set_block(3, -2, Some(Block::Garbage));
+
+        board.clear_above(BOARD_HEIGHT / 2);
+        assert!(!board.is_buffer_occupied());
+        assert_eq!(board.garbage_rows(), (BOARD_HEIGHT / 2) as u32);
+        assert!(!board.has_block(5, BOARD_HEIGHT / 2 - 1));
+        assert!(board.has_block(5, BOARD_HEIGHT / 2));
+    }
+
+    #[test]
+    fn test_lock_times_move_with_cleared_lines() {
+        let mut board = Board::new();
+        assert!(board.add_garbage(1, 0));
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.y += BOARD_HEIGHT - 2 - piece.get_blocks()[0].1;
+        board.place_piece_at(&piece, 4.5);
+        board.set_block(0, BOARD_HEIGHT - 1, Some(Block::Garbage));
+
+        assert_eq!(board.clear_lines(), 1);
+        for (x, y) in piece.get_blocks() {
+            assert_eq!(
+                board.get_block(x, y + 1),
+                Some(Block::Piece(TetrominoType::I))
+            );
+            assert_eq!(board.locked_at(x, y + 1), 4.5);
+        }
+        assert_eq!(board.locked_at(0, BOARD_HEIGHT - 1), 0.0);
+    }
+
+    #[test]
+    fn test_vacated_cells_have_no_lock_time() {
+        // A clear empties the top row under a piece locked there.
+        let mut board = Board::new();
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.y += -BUFFER_HEIGHT - piece.get_blocks()[0].1;
+        board.place_piece_at(&piece, 4.5);
+        for x in 0..BOARD_WIDTH {
+            board.set_block(x, BOARD_HEIGHT - 1, Some(Block::Garbage));
+        }
+        assert_eq!(board.clear_lines(), 1);
+        for (x, y) in piece.get_blocks() {
+            assert!(!board.has_block(x, y));
+            assert_eq!(board.locked_at(x, y), 0.0);
+            assert_eq!(board.locked_at(x, y + 1), 4.5);
+        }
+
+        // Garbage pushed in under a piece is not stamped with its time.
+        let mut board = Board::new();
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.y += BOARD_HEIGHT - 1 - piece.get_blocks()[0].1;
+        board.place_piece_at(&piece, 3.0);
+        assert!(board.add_garbage(1, 9));
+        for (x, y) in piece.get_blocks() {
+            assert_eq!(board.get_block(x, y), Some(Block::Garbage));
+            assert_eq!(board.locked_at(x, y), 0.0);
+            assert_eq!(board.locked_at(x, y - 1), 3.0);
+        }
+    }
 }
@@ -0,0 +1,155 @@
+use super::board::Board;
+use super::ruleset::Ruleset;
+use crate::ai::{find_placements, Input};
+use crate::tetromino::{Tetromino, TetrominoType};
+
+/// Whether an input counts toward finesse: movement and rotation do, drops
+/// do not.
+pub fn counts(input: Input) -> bool {
+    !matches!(input, Input::SoftDrop | Input::HardDrop)
+}
+
+/// Short name of an input for on-screen sequences.
+pub fn input_label(input: Input) -> &'static str {
+    match input {
+        Input::Left => "L",
+        Input::Right => "R",
+        Input::DasLeft => "DAS L",
+        Input::DasRight => "DAS R",
+        Input::RotateCw => "CW",
+        Input::RotateCcw => "CCW",
+        Input::SoftDrop => "SOFT",
+        Input::HardDrop => "DROP",
+    }
+}
+
+/// A piece locked with more key presses than it needed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FinesseFault {
+    pub piece: Tetromino,
+    /// The presses the player used, in order.
+    pub used: Vec<Input>,
+    /// The fewest presses that get there on an empty board.
+    pub optimal: Vec<Input>,
+    /// Game time the piece locked.
+    pub time: f32,
+}
+
+/// The finesse table: for every piece, column and orientation, the fewest
+/// movement and rotation presses that reach it from spawn on an empty
+/// board, with DAS to either wall counting as one press. Worked out with
+/// the move generator rather than written down, so it fits any board width.
+pub struct FinesseTable {
+    /// Each placement where it lands on the empty board, its shape and the
+    /// presses it takes.
+    entries: Vec<(Tetromino, Shape, Vec<Input>)>,
+}
+
+impl FinesseTable {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        let board = Board::with_ruleset(ruleset);
+        let entries = TetrominoType::all()
+            .into_iter()
+            .flat_map(|piece_type| {
+                let spawn = Tetromino::spawn(piece_type, board.width());
+                find_placements(&board, &spawn)
+                    .into_iter()
+                    .map(move |placement| {
+                        let inputs = placement.inputs.into_iter().filter(|&i| counts(i));
+                        (placement.piece, shape(&placement.piece), inputs.collect())
+                    })
+            })
+            .collect();
+        FinesseTable { entries }
+    }
+
+    /// The fewest presses that put `piece` in its columns and orientation,
+    /// wherever it ended up vertically.
+    pub fn optimal(&self, piece: &Tetromino) -> Option<&[Input]> {
+        let shape = shape(piece);
+        self.entries
+            .iter()
+            .find(|(target, cells, _)| target.piece_type == piece.piece_type && *cells == shape)
+            .map(|(_, _, inputs)| inputs.as_slice())
+    }
+
+    /// Every distinct place a piece of `piece_type` can land on the empty
+    /// board, in order of how many presses it takes.
+    pub fn placements(&self, piece_type: TetrominoType) -> Vec<Tetromino> {
+        self.entries
+            .iter()
+            .filter(|(piece, _, _)| piece.piece_type == piece_type)
+            .map(|(piece, _, _)| *piece)
+            .collect()
+    }
+
+    /// The fault, if `used` took more presses than the table allows for
+    /// `piece`.
+    pub fn check(&self, piece: &Tetromino, used: &[Input], time: f32) -> Option<FinesseFault> {
+        let optimal = self.optimal(piece)?;
+        let presses = used.iter().filter(|&&input| counts(input)).count();
+        (presses > optimal.len()).then(|| FinesseFault {
+            piece: *piece,
+            used: used.to_vec(),
+            optimal: optimal.to_vec(),
+            time,
+        })
+    }
+}
+
+/// A piece's cells, sorted and lifted so the lowest sits on row 0, which
+/// identifies its columns and orientation whatever its height.
+pub type Shape = [(i32, i32); 4];
+
+/// The `Shape` of a piece where it is.
+pub fn shape(piece: &Tetromino) -> Shape {
+    let mut cells = piece.get_blocks();
+    cells.sort_unstable();
+    let bottom = cells.iter().map(|&(_, y)| y).max().unwrap_or(0);
+    cells.map(|(x, y)| (x, y - bottom))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placed(piece_type: TetrominoType, rotation: u8, dx: i32) -> Tetromino {
+        let mut piece = Tetromino::new(piece_type);
+        piece.rotation = rotation;
+        piece.move_by(dx, 10);
+        piece
+    }
+
+    #[test]
+    fn test_table_matches_two_step_finesse() {
+        let table = FinesseTable::new(&Ruleset::standard());
+        let presses = |piece: Tetromino| table.optimal(&piece).map(<[Input]>::len);
+
+        // Straight down, one tap, DAS to the wall, DAS and tap back.
+        assert_eq!(presses(placed(TetrominoType::O, 0, 0)), Some(0));
+        assert_eq!(presses(placed(TetrominoType::O, 0, -1)), Some(1));
+        assert_eq!(presses(placed(TetrominoType::O, 0, -4)), Some(1));
+        assert_eq!(presses(placed(TetrominoType::O, 0, -3)), Some(2));
+        // A flat T three columns right: DAS then tap back, not three taps.
+        assert_eq!(presses(placed(TetrominoType::T, 0, 3)), Some(2));
+        // A vertical I against the left wall: rotate either way and DAS.
+        let mut piece = placed(TetrominoType::I, 3, 0);
+        let left = piece.get_blocks().iter().map(|&(x, _)| x).min().unwrap();
+        piece.move_by(-left, 0);
+        assert_eq!(presses(piece), Some(2));
+    }
+
+    #[test]
+    fn test_check_counts_only_movement_and_rotation() {
+        let table = FinesseTable::new(&Ruleset::standard());
+        let piece = placed(TetrominoType::O, 0, -3);
+
+        let clean = [Input::DasLeft, Input::Right, Input::SoftDrop];
+        assert_eq!(table.check(&piece, &clean, 1.0), None);
+
+        let taps = [Input::Left, Input::Left, Input::Left];
+        let fault = table.check(&piece, &taps, 1.0).unwrap();
+        assert_eq!(fault.optimal.len(), 2);
+        assert_eq!(fault.used, taps);
+    }
+}
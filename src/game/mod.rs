@@ -1,5 +1,15 @@
 pub mod board;
+pub mod finesse;
+pub mod mode;
+pub mod ruleset;
 pub mod scoring;
 pub mod state;
+pub mod stats;
+pub mod timing;
 
+pub use finesse::{FinesseFault, FinesseTable};
+pub use mode::{GameMode, Goal, Outcome};
+pub use ruleset::{Ruleset, StackVisibility};
 pub use state::GameState;
+pub use stats::GameStats;
+pub use timing::Timing;
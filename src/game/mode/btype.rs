@@ -0,0 +1,139 @@
+use super::{GameMode, Goal, ModeContext, ModeRng};
+use crate::config::{BTYPE_BONUS_PER_LEVEL, BTYPE_HIGH_ROWS, BTYPE_LINES};
+use crate::game::board::Board;
+use crate::game::scoring::Scoring;
+use crate::game::stats::format_time;
+
+/// The Game Boy's B-Type game: start on a chosen level with a chosen
+/// "high" of random garbage, and clear 25 lines to win. Gravity stays at
+/// the starting level, and finishing pays a bonus for level and height.
+pub struct BType {
+    level: u32,
+    high: u32,
+    rng: ModeRng,
+}
+
+impl BType {
+    /// `level` uses the side panel's numbering (1-10, the Game Boy's 0-9)
+    /// and `high` is 0-5; both are clamped into range.
+    pub fn new(level: u32, high: u32) -> Self {
+        BType {
+            level: level.clamp(1, 10),
+            high: high.min(BTYPE_HIGH_ROWS.len() as u32 - 1),
+            rng: ModeRng::default(),
+        }
+    }
+
+    pub fn garbage_rows(&self) -> u32 {
+        BTYPE_HIGH_ROWS[self.high as usize]
+    }
+
+    /// Bonus for winning, growing with both the level and the height.
+    pub fn bonus(&self) -> u32 {
+        BTYPE_BONUS_PER_LEVEL * self.level * (self.high + 1)
+    }
+}
+
+impl GameMode for BType {
+    fn name(&self) -> &'static str {
+        "btype"
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = ModeRng::seeded(seed);
+    }
+
+    fn setup_board(&mut self, board: &mut Board) {
+        let full_row = board.full_row_mask();
+        let width = board.width();
+
+        let rng = &self.rng;
+        board.add_garbage_rows(self.garbage_rows(), |_| {
+            let mask = rng.gen_range(0, full_row) & full_row;
+            let hole = rng.gen_range(0, width);
+            mask & !(1 << hole)
+        });
+    }
+
+    fn starting_level(&self) -> u32 {
+        self.level
+    }
+
+    fn drop_interval(&self, _scoring: &Scoring) -> f32 {
+        Scoring::drop_speed_for_level(self.level)
+    }
+
+    fn goal(&self) -> Goal {
+        Goal::Lines(BTYPE_LINES)
+    }
+
+    fn completion_bonus(&self, _ctx: &ModeContext) -> u32 {
+        self.bonus()
+    }
+
+    fn completion_title(&self) -> &'static str {
+        "CONGRATULATIONS!"
+    }
+
+    fn hud(&self, _ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![("HIGH", self.high.to_string())]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let lines_left = BTYPE_LINES.saturating_sub(ctx.scoring.lines_cleared);
+        let mut results = vec![
+            ("LEVEL", self.level.to_string()),
+            ("HIGH", self.high.to_string()),
+            ("SCORE", ctx.scoring.score.to_string()),
+        ];
+        if lines_left == 0 {
+            results.push(("BONUS", self.bonus().to_string()));
+        } else {
+            results.push(("LINES LEFT", lines_left.to_string()));
+        }
+        results.push(("TIME", format_time(ctx.stats.elapsed)));
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::mode::Outcome;
+    use crate::game::ruleset::Ruleset;
+    use crate::game::GameState;
+
+    #[test]
+    fn test_high_fills_garbage_with_holes() {
+        let mut mode = BType::new(3, 4);
+        let mut board = Board::new();
+        mode.setup_board(&mut board);
+
+        let rows = board.rows();
+        let garbage = mode.garbage_rows() as usize;
+        let (empty, filled) = rows.split_at(rows.len() - garbage);
+        assert!(empty.iter().all(|&row| row == 0));
+        assert!(filled.iter().all(|&row| row != board.full_row_mask()));
+    }
+
+    #[test]
+    fn test_victory_pays_bonus() {
+        let mode = BType::new(5, 2);
+        let bonus = mode.bonus();
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(mode));
+        assert_eq!(state.scoring.level, 5);
+
+        state.scoring.lines_cleared = BTYPE_LINES;
+        state.update(0.01);
+
+        assert_eq!(state.outcome, Some(Outcome::Completed));
+        assert_eq!(state.scoring.score, bonus);
+    }
+
+    #[test]
+    fn test_settings_are_clamped() {
+        let mode = BType::new(0, 9);
+        assert_eq!(mode.level, 1);
+        assert_eq!(mode.garbage_rows(), 12);
+    }
+}
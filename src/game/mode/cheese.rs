@@ -0,0 +1,121 @@
+use super::{GameMode, ModeContext, ModeRng, Outcome};
+use crate::config::{COUNTDOWN_SECONDS, INITIAL_DROP_SPEED};
+use crate::game::board::Board;
+use crate::game::scoring::Scoring;
+use crate::game::stats::format_time;
+
+/// Cheese race: dig through rows of messy garbage, each with one hole in a
+/// different column from the row below, as fast as possible. The race ends
+/// once no garbage cell is left on the board.
+pub struct Cheese {
+    rows: u32,
+    rng: ModeRng,
+}
+
+impl Cheese {
+    /// A race through `rows` garbage rows, clamped to leave room to play
+    /// on the board it is set up on.
+    pub fn new(rows: u32) -> Self {
+        Cheese {
+            rows: rows.max(1),
+            rng: ModeRng::default(),
+        }
+    }
+}
+
+impl GameMode for Cheese {
+    fn name(&self) -> &'static str {
+        "cheese"
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = ModeRng::seeded(seed);
+    }
+
+    fn setup_board(&mut self, board: &mut Board) {
+        self.rows = self.rows.min((board.height() - 4).max(1) as u32);
+
+        let width = board.width();
+        let mut previous_hole = self.rng.gen_range(0, width);
+        board.add_garbage_with(self.rows, |_| {
+            let hole = (previous_hole + self.rng.gen_range(1, width)) % width;
+            previous_hole = hole;
+            hole
+        });
+    }
+
+    fn drop_interval(&self, _scoring: &Scoring) -> f32 {
+        INITIAL_DROP_SPEED
+    }
+
+    fn countdown(&self) -> f32 {
+        COUNTDOWN_SECONDS
+    }
+
+    fn check_end(&mut self, ctx: &ModeContext) -> Option<Outcome> {
+        (ctx.board.garbage_rows() == 0).then_some(Outcome::Completed)
+    }
+
+    fn hud(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![("GARBAGE", format!("{:02}", ctx.board.garbage_rows()))]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let dug = self.rows.saturating_sub(ctx.board.garbage_rows());
+        vec![
+            ("TIME", format_time(ctx.stats.elapsed)),
+            ("ROWS DUG", format!("{dug}/{}", self.rows)),
+            ("PIECES", ctx.stats.pieces_placed.to_string()),
+            ("PPS", format!("{:.2}", ctx.stats.pieces_per_second())),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ruleset::Ruleset;
+    use crate::game::GameState;
+
+    #[test]
+    fn test_setup_adds_messy_single_hole_rows() {
+        let mut mode = Cheese::new(8);
+        let mut board = Board::new();
+        mode.setup_board(&mut board);
+
+        assert_eq!(board.garbage_rows(), 8);
+        let rows = board.rows();
+        let garbage = &rows[rows.len() - 8..];
+        for pair in garbage.windows(2) {
+            assert_eq!(pair[0].count_ones(), board.width() as u32 - 1);
+            assert_ne!(pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn test_rows_are_clamped_to_board() {
+        let mut mode = Cheese::new(100);
+        let mut board = Board::new();
+        mode.setup_board(&mut board);
+        assert_eq!(board.garbage_rows(), board.height() as u32 - 4);
+    }
+
+    #[test]
+    fn test_seed_repeats_the_garbage() {
+        let game = |seed| GameState::with_seed(Ruleset::standard(), Box::new(Cheese::new(8)), seed);
+        assert_eq!(game(4).board.rows(), game(4).board.rows());
+        assert_ne!(game(4).board.rows(), game(5).board.rows());
+    }
+
+    #[test]
+    fn test_race_ends_when_garbage_is_gone() {
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(Cheese::new(2)));
+        state.update(COUNTDOWN_SECONDS);
+        state.update(0.01);
+        assert!(!state.game_over);
+
+        state.board.clear();
+        state.update(0.01);
+        assert_eq!(state.outcome, Some(Outcome::Completed));
+    }
+}
@@ -0,0 +1,11 @@
+use super::GameMode;
+
+/// The original endless game: levels rise every ten lines and play goes on
+/// until the stack tops out.
+pub struct Endless;
+
+impl GameMode for Endless {
+    fn name(&self) -> &'static str {
+        "endless"
+    }
+}
@@ -0,0 +1,367 @@
+use super::{GameMode, ModeContext, ModeRng, Outcome};
+use crate::config::FINESSE_TRAINER_TARGETS;
+use crate::game::board::Board;
+use crate::game::finesse::{shape, FinesseFault, FinesseTable};
+use crate::game::ruleset::Ruleset;
+use crate::game::scoring::Scoring;
+use crate::game::stats::format_time;
+use crate::game::timing::Timing;
+use crate::tetromino::{Tetromino, TetrominoType};
+
+/// Targets hit out of those tried.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub hits: u32,
+    pub attempts: u32,
+}
+
+impl Tally {
+    /// Fraction of attempts that hit, 0 before any.
+    pub fn accuracy(&self) -> f32 {
+        if self.attempts > 0 {
+            self.hits as f32 / self.attempts as f32
+        } else {
+            0.0
+        }
+    }
+
+    fn percent(&self) -> String {
+        format!("{:.0}%", self.accuracy() * 100.0)
+    }
+}
+
+/// Finesse practice on an empty board with no gravity. Each piece comes
+/// with a target placement, drawn as an outline, to reach with the fewest
+/// presses the finesse table allows. A hit moves on to a new target; a
+/// fault or a miss deals the same piece at the same target again. The
+/// session ends once enough targets are hit.
+pub struct FinesseTrainer {
+    goal: u32,
+    table: Option<FinesseTable>,
+    target: Option<Tetromino>,
+    /// Whether the next spawn is a retry of the current target.
+    retrying: bool,
+    streak: u32,
+    /// Results per piece type and target column (its leftmost cell).
+    tallies: Vec<(TetrominoType, i32, Tally)>,
+    rng: ModeRng,
+}
+
+impl FinesseTrainer {
+    pub fn new() -> Self {
+        Self::with_goal(FINESSE_TRAINER_TARGETS)
+    }
+
+    /// A session that ends after `goal` targets are hit.
+    pub fn with_goal(goal: u32) -> Self {
+        FinesseTrainer {
+            goal,
+            table: None,
+            target: None,
+            retrying: false,
+            streak: 0,
+            tallies: Vec::new(),
+            rng: ModeRng::default(),
+        }
+    }
+
+    /// Results for targets of `piece_type` in `column`, either of which
+    /// may be left out to add up over all of them.
+    pub fn tally(&self, piece_type: Option<TetrominoType>, column: Option<i32>) -> Tally {
+        self.tallies
+            .iter()
+            .filter(|(t, c, _)| {
+                piece_type.is_none_or(|p| p == *t) && column.is_none_or(|x| x == *c)
+            })
+            .fold(Tally::default(), |total, (_, _, tally)| Tally {
+                hits: total.hits + tally.hits,
+                attempts: total.attempts + tally.attempts,
+            })
+    }
+
+    fn record(&mut self, target: &Tetromino, hit: bool) {
+        let column = left_column(target);
+        let index = match self
+            .tallies
+            .iter()
+            .position(|&(t, c, _)| t == target.piece_type && c == column)
+        {
+            Some(index) => index,
+            None => {
+                self.tallies
+                    .push((target.piece_type, column, Tally::default()));
+                self.tallies.len() - 1
+            }
+        };
+        let tally = &mut self.tallies[index].2;
+        tally.attempts += 1;
+        tally.hits += hit as u32;
+    }
+
+    /// The piece type and column tried most often with the lowest accuracy.
+    fn weakest(&self) -> Option<&(TetrominoType, i32, Tally)> {
+        self.tallies.iter().min_by(|a, b| {
+            a.2.accuracy()
+                .total_cmp(&b.2.accuracy())
+                .then(b.2.attempts.cmp(&a.2.attempts))
+        })
+    }
+}
+
+impl Default for FinesseTrainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Results rows giving the accuracy of each named tally tried, two to a
+/// row, the first row under `label`.
+fn two_per_row(
+    label: &'static str,
+    tallies: impl Iterator<Item = (String, Tally)>,
+) -> Vec<(&'static str, String)> {
+    let tried: Vec<String> = tallies
+        .filter(|(_, tally)| tally.attempts > 0)
+        .map(|(name, tally)| format!("{name} {}", tally.percent()))
+        .collect();
+    tried
+        .chunks(2)
+        .enumerate()
+        .map(|(row, pair)| (if row == 0 { label } else { "" }, pair.join("  ")))
+        .collect()
+}
+
+fn left_column(piece: &Tetromino) -> i32 {
+    piece
+        .get_blocks()
+        .iter()
+        .map(|&(x, _)| x)
+        .min()
+        .unwrap_or(0)
+}
+
+impl GameMode for FinesseTrainer {
+    fn name(&self) -> &'static str {
+        "finesse"
+    }
+
+    fn reset(&mut self) {
+        *self = Self::with_goal(self.goal);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = ModeRng::seeded(seed);
+    }
+
+    fn setup_board(&mut self, board: &mut Board) {
+        let ruleset = Ruleset::new(board.width(), board.height());
+        self.table = Some(FinesseTable::new(&ruleset));
+    }
+
+    fn timing(&self, _scoring: &Scoring) -> Timing {
+        Timing {
+            gravity: 0.0,
+            lock_delay: u32::MAX,
+            are: 0,
+            line_clear_delay: 0,
+        }
+    }
+
+    fn on_spawn(&mut self, piece: &Tetromino) {
+        let same_piece = self
+            .target
+            .is_some_and(|target| target.piece_type == piece.piece_type);
+        if std::mem::take(&mut self.retrying) && same_piece {
+            return;
+        }
+
+        let targets = self
+            .table
+            .as_ref()
+            .map(|table| table.placements(piece.piece_type))
+            .unwrap_or_default();
+        self.target = (!targets.is_empty()).then(|| targets[self.rng.gen_range(0, targets.len())]);
+    }
+
+    fn on_piece_placed(
+        &mut self,
+        piece: &Tetromino,
+        fault: Option<&FinesseFault>,
+        board: &mut Board,
+    ) -> bool {
+        board.clear_above(board.height());
+        let Some(target) = self.target else {
+            return false;
+        };
+
+        let hit = fault.is_none() && shape(piece) == shape(&target);
+        self.record(&target, hit);
+        self.streak = if hit { self.streak + 1 } else { 0 };
+        self.retrying = !hit;
+        self.retrying
+    }
+
+    fn target(&self) -> Option<Tetromino> {
+        self.target
+    }
+
+    fn shows_finesse(&self) -> bool {
+        true
+    }
+
+    fn check_end(&mut self, _ctx: &ModeContext) -> Option<Outcome> {
+        (self.tally(None, None).hits >= self.goal).then_some(Outcome::Completed)
+    }
+
+    fn completion_title(&self) -> &'static str {
+        "WELL DONE!"
+    }
+
+    fn hud(&self, _ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let total = self.tally(None, None);
+        vec![
+            ("TARGETS", format!("{}/{}", total.hits, self.goal)),
+            ("STREAK", self.streak.to_string()),
+            ("ACCURACY", total.percent()),
+        ]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let total = self.tally(None, None);
+        let mut results = vec![
+            ("HITS", total.hits.to_string()),
+            ("ATTEMPTS", total.attempts.to_string()),
+            ("ACCURACY", total.percent()),
+            ("TIME", format_time(ctx.stats.elapsed)),
+        ];
+        let pieces = TetrominoType::all().into_iter().map(|piece_type| {
+            (
+                piece_type.letter().to_string(),
+                self.tally(Some(piece_type), None),
+            )
+        });
+        results.extend(two_per_row("PIECES", pieces));
+        let mut columns: Vec<i32> = self.tallies.iter().map(|&(_, column, _)| column).collect();
+        columns.sort_unstable();
+        columns.dedup();
+        let columns = columns
+            .into_iter()
+            .map(|column| ((column + 1).to_string(), self.tally(None, Some(column))));
+        results.extend(two_per_row("COLUMNS", columns));
+        if let Some((piece_type, column, tally)) = self.weakest() {
+            let weakest = format!(
+                "{} COL {} {}",
+                piece_type.letter(),
+                column + 1,
+                tally.percent()
+            );
+            results.push(("WEAKEST", weakest));
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::Input;
+    use crate::game::stats::GameStats;
+    use crate::game::GameState;
+
+    fn trainer(goal: u32) -> GameState {
+        GameState::with_mode(
+            Ruleset::standard(),
+            Box::new(FinesseTrainer::with_goal(goal)),
+        )
+    }
+
+    /// Drop the current piece straight onto its target.
+    fn hit(state: &mut GameState) {
+        state.current_piece = state.mode().target().expect("a target");
+        state.hard_drop();
+    }
+
+    #[test]
+    fn test_target_follows_the_piece() {
+        let state = trainer(10);
+        let target = state.mode().target().unwrap();
+        assert_eq!(target.piece_type, state.current_piece.piece_type);
+        assert!(state.board.is_valid_position(&target));
+    }
+
+    #[test]
+    fn test_seed_repeats_the_targets() {
+        let targets = |seed| {
+            let mut state = GameState::with_seed(
+                Ruleset::standard(),
+                Box::new(FinesseTrainer::with_goal(10)),
+                seed,
+            );
+            (0..5)
+                .map(|_| {
+                    let target = state.mode().target();
+                    hit(&mut state);
+                    target
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(targets(7), targets(7));
+    }
+
+    #[test]
+    fn test_fault_retries_the_same_target() {
+        let mut state = trainer(10);
+        let (piece_type, target) = (state.current_piece.piece_type, state.mode().target());
+
+        // Wasted presses, then the right spot: still a fault.
+        state.press(Input::RotateCw);
+        state.press(Input::RotateCcw);
+        state.current_piece = target.unwrap();
+        state.hard_drop();
+
+        assert_eq!(state.current_piece.piece_type, piece_type);
+        assert_eq!(state.mode().target(), target);
+        assert!(state.board.rows().iter().all(|&row| row == 0));
+
+        hit(&mut state);
+        let hud = state.hud();
+        assert_eq!(hud[0], ("TARGETS", "1/10".to_owned()));
+        assert_eq!(hud[2], ("ACCURACY", "50%".to_owned()));
+    }
+
+    #[test]
+    fn test_session_ends_after_goal() {
+        let mut state = trainer(2);
+        hit(&mut state);
+        assert!(!state.game_over);
+        hit(&mut state);
+        assert_eq!(state.outcome, Some(Outcome::Completed));
+        assert_eq!(state.results()[2], ("ACCURACY", "100%".to_owned()));
+    }
+
+    #[test]
+    fn test_tally_by_piece_and_column() {
+        let mut mode = FinesseTrainer::new();
+        let mut target = Tetromino::new(TetrominoType::T);
+        target.x = 1;
+        mode.record(&target, true);
+        mode.record(&target, false);
+        target.x = 6;
+        mode.record(&target, true);
+
+        let column = left_column(&target);
+        assert_eq!(mode.tally(Some(TetrominoType::T), None).attempts, 3);
+        assert_eq!(mode.tally(None, Some(column)).accuracy(), 1.0);
+        assert_eq!(mode.tally(Some(TetrominoType::I), None), Tally::default());
+        assert_eq!(mode.weakest().unwrap().2.hits, 1);
+
+        let results = mode.results(&ModeContext {
+            board: &Board::new(),
+            scoring: &Scoring::new(),
+            stats: &GameStats::new(),
+            hold: None,
+        });
+        assert_eq!(results[4], ("PIECES", "T 67%".to_owned()));
+        assert_eq!(results[5], ("COLUMNS", "2 50%  7 100%".to_owned()));
+    }
+}
@@ -0,0 +1,160 @@
+use super::{GameMode, Goal, ModeContext};
+use crate::config::{
+    FRAMES_PER_SECOND, MARATHON_LINES, MARATHON_LOCK_DELAY, MARATHON_LONG_LINES, MARATHON_MAX_LEVEL,
+};
+use crate::game::scoring::{LevelProgression, Scoring};
+use crate::game::stats::format_time;
+use crate::game::timing::Timing;
+
+/// Guideline Marathon: clear 150 (or 200) lines with gravity rising level
+/// by level, on the guideline curve, up to a cap. Levels go up every ten lines, or by the variable
+/// goal of 5 × level lines when that is chosen.
+pub struct Marathon {
+    start_level: u32,
+    lines: u32,
+    progression: LevelProgression,
+}
+
+impl Marathon {
+    /// `lines` picks the 150- or 200-line game; anything above 150 plays
+    /// the longer one. The 200-line game raises the level cap by five.
+    pub fn new(start_level: u32, lines: u32, variable_goal: bool) -> Self {
+        let lines = if lines > MARATHON_LINES {
+            MARATHON_LONG_LINES
+        } else {
+            MARATHON_LINES
+        };
+        let progression = if variable_goal {
+            LevelProgression::Variable
+        } else {
+            LevelProgression::default()
+        };
+
+        Marathon {
+            start_level: start_level.clamp(1, MARATHON_MAX_LEVEL),
+            lines,
+            progression,
+        }
+    }
+
+    /// Highest level the game can reach.
+    pub fn max_level(&self) -> u32 {
+        MARATHON_MAX_LEVEL + (self.lines - MARATHON_LINES) / 10
+    }
+}
+
+/// Seconds per row on the guideline gravity curve, which keeps getting
+/// faster past the point where the default curve bottoms out.
+fn drop_interval_for_level(level: u32) -> f32 {
+    let level = level.saturating_sub(1) as f32;
+    (0.8 - level * 0.007).powf(level)
+}
+
+impl GameMode for Marathon {
+    fn name(&self) -> &'static str {
+        "marathon"
+    }
+
+    fn starting_level(&self) -> u32 {
+        self.start_level
+    }
+
+    fn create_scoring(&self) -> Scoring {
+        Scoring::with_start_level(self.start_level)
+            .with_progression(self.progression)
+            .with_level_cap(self.max_level())
+    }
+
+    fn drop_interval(&self, scoring: &Scoring) -> f32 {
+        drop_interval_for_level(scoring.level)
+    }
+
+    /// Guideline gravity with a fixed lock delay, so the fast levels near
+    /// the cap stay playable.
+    fn timing(&self, scoring: &Scoring) -> Timing {
+        Timing {
+            gravity: 1.0 / (self.drop_interval(scoring) * FRAMES_PER_SECOND),
+            lock_delay: MARATHON_LOCK_DELAY,
+            are: 0,
+            line_clear_delay: 0,
+        }
+    }
+
+    fn goal(&self) -> Goal {
+        Goal::Lines(self.lines)
+    }
+
+    fn completion_title(&self) -> &'static str {
+        "CONGRATULATIONS!"
+    }
+
+    fn hud(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let next = ctx
+            .scoring
+            .lines_to_next_level()
+            .map_or_else(|| "MAX".to_owned(), |lines| lines.to_string());
+        vec![("NEXT LV", next)]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![
+            ("SCORE", ctx.scoring.score.to_string()),
+            (
+                "LINES",
+                format!("{}/{}", ctx.scoring.lines_cleared, self.lines),
+            ),
+            ("LEVEL", ctx.scoring.level.to_string()),
+            ("TIME", format_time(ctx.stats.elapsed)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_goals_and_caps() {
+        let marathon = Marathon::new(1, 150, false);
+        assert_eq!(marathon.goal(), Goal::Lines(150));
+        assert_eq!(marathon.max_level(), 15);
+
+        let marathon = Marathon::new(1, 200, false);
+        assert_eq!(marathon.goal(), Goal::Lines(200));
+        assert_eq!(marathon.max_level(), 20);
+    }
+
+    #[test]
+    fn test_scoring_stops_at_level_cap() {
+        let mut scoring = Marathon::new(1, 150, false).create_scoring();
+        scoring.add_lines(149);
+        assert_eq!(scoring.level, 15);
+        assert_eq!(scoring.lines_to_next_level(), None);
+    }
+
+    #[test]
+    fn test_gravity_speeds_up_to_the_cap() {
+        let marathon = Marathon::new(1, 200, false);
+        let mut scoring = marathon.create_scoring();
+        let mut gravity = marathon.timing(&scoring).gravity;
+        assert!((gravity - 1.0 / FRAMES_PER_SECOND).abs() < 1e-6);
+        while scoring.level < marathon.max_level() {
+            scoring.add_lines(10);
+            let timing = marathon.timing(&scoring);
+            assert!(timing.gravity > gravity, "level {}", scoring.level);
+            assert_eq!(timing.lock_delay, MARATHON_LOCK_DELAY);
+            gravity = timing.gravity;
+        }
+        assert!(gravity > 20.0);
+    }
+
+    #[test]
+    fn test_variable_goal_scoring() {
+        let mut scoring = Marathon::new(3, 150, true).create_scoring();
+        assert_eq!(scoring.level, 3);
+        assert_eq!(scoring.lines_to_next_level(), Some(15));
+
+        scoring.add_lines(15);
+        assert_eq!(scoring.level, 4);
+    }
+}
@@ -0,0 +1,379 @@
+use super::{GameMode, ModeContext, Outcome};
+use crate::config::{
+    COUNTDOWN_SECONDS, MASTER_DECAY_SECONDS, MASTER_DELAYS, MASTER_GM_SECONDS, MASTER_GRADES,
+    MASTER_GRADE_POINTS, MASTER_GRAVITY, MASTER_MAX_LEVEL, MASTER_POINTS_PER_GRADE,
+    MASTER_SECTION_LEVELS,
+};
+use crate::game::scoring::Scoring;
+use crate::game::stats::format_time;
+use crate::game::timing::Timing;
+
+const SECTION_LABELS: [&str; 10] = [
+    "000-099", "100-199", "200-299", "300-399", "400-499", "500-599", "600-699", "700-799",
+    "800-899", "900-999",
+];
+
+/// High-gravity mode after Tetris The Grand Master. A 0-999 level counter
+/// goes up by one per piece and by the lines of each clear, but only a
+/// clear gets it past a section stop at x99 or 998. Gravity ramps up to
+/// 20G by level 500, after which the entry and lock delays shrink. The
+/// grade is kept hidden until the results screen, next to each section's
+/// time and the best time for that section so far.
+pub struct Master {
+    level: u32,
+    /// Index into `MASTER_GRADES`.
+    grade: usize,
+    /// Points toward the next grade; they drain away between clears.
+    grade_points: u32,
+    decay_time: f32,
+    last_lock: f32,
+    grand_master: bool,
+    section_start: f32,
+    section_times: Vec<f32>,
+    /// Best time for each section over the earlier games this session;
+    /// kept across resets.
+    section_records: Vec<f32>,
+}
+
+impl Master {
+    pub fn new() -> Self {
+        Master {
+            level: 0,
+            grade: 0,
+            grade_points: 0,
+            decay_time: 0.0,
+            last_lock: 0.0,
+            grand_master: false,
+            section_start: 0.0,
+            section_times: Vec::new(),
+            section_records: Vec::new(),
+        }
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Name of the grade earned so far.
+    pub fn grade(&self) -> &'static str {
+        if self.grand_master {
+            "GM"
+        } else {
+            MASTER_GRADES[self.grade]
+        }
+    }
+
+    /// Seconds taken over each finished section of 100 levels.
+    pub fn section_times(&self) -> &[f32] {
+        &self.section_times
+    }
+
+    /// Best time for each section in the games before this one.
+    pub fn section_records(&self) -> &[f32] {
+        &self.section_records
+    }
+
+    /// Fold this game's section times into the records.
+    fn keep_records(&mut self) {
+        for (index, &time) in self.section_times.iter().enumerate() {
+            match self.section_records.get_mut(index) {
+                Some(record) => *record = record.min(time),
+                None => self.section_records.push(time),
+            }
+        }
+    }
+
+    /// Whether a piece alone can't take the level any further.
+    fn at_section_stop(&self) -> bool {
+        self.level % MASTER_SECTION_LEVELS == MASTER_SECTION_LEVELS - 1
+            || self.level == MASTER_MAX_LEVEL - 1
+    }
+
+    /// Level at which the current section stops.
+    fn next_stop(&self) -> u32 {
+        let stop = (self.level / MASTER_SECTION_LEVELS + 1) * MASTER_SECTION_LEVELS - 1;
+        stop.min(MASTER_MAX_LEVEL)
+    }
+
+    fn advance_level(&mut self, lines: u32, elapsed: f32) {
+        let before = self.level;
+        if !self.at_section_stop() {
+            self.level += 1;
+        }
+        self.level = (self.level + lines).min(MASTER_MAX_LEVEL);
+
+        let new_section = self.level / MASTER_SECTION_LEVELS > before / MASTER_SECTION_LEVELS;
+        if new_section || (self.level == MASTER_MAX_LEVEL && before != MASTER_MAX_LEVEL) {
+            self.section_times.push(elapsed - self.section_start);
+            self.section_start = elapsed;
+        }
+    }
+
+    fn decay_grade(&mut self, seconds: f32) {
+        if self.grade_points == 0 {
+            self.decay_time = 0.0;
+            return;
+        }
+
+        let interval = self.decay_interval();
+        self.decay_time += seconds;
+        while self.decay_time >= interval && self.grade_points > 0 {
+            self.decay_time -= interval;
+            self.grade_points -= 1;
+        }
+    }
+
+    /// Seconds for one grade point to drain, quicker at higher grades.
+    fn decay_interval(&self) -> f32 {
+        MASTER_DECAY_SECONDS / (1.0 + self.grade as f32 / 3.0)
+    }
+
+    /// Award grade points for a clear, more of them deeper into the game.
+    fn award_grade_points(&mut self, lines: u32) {
+        let tier = (self.grade / 6).min(MASTER_GRADE_POINTS.len() - 1);
+        let lines = lines.clamp(1, 4) as usize;
+        let level_multiplier = self.level / 250 + 1;
+        self.grade_points += MASTER_GRADE_POINTS[tier][lines - 1] * level_multiplier;
+
+        if self.grade_points >= MASTER_POINTS_PER_GRADE {
+            self.grade_points = 0;
+            self.decay_time = 0.0;
+            self.grade = (self.grade + 1).min(MASTER_GRADES.len() - 1);
+        }
+    }
+}
+
+impl Default for Master {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameMode for Master {
+    fn name(&self) -> &'static str {
+        "master"
+    }
+
+    fn reset(&mut self) {
+        self.keep_records();
+        *self = Master {
+            section_records: std::mem::take(&mut self.section_records),
+            ..Master::new()
+        };
+    }
+
+    fn timing(&self, _scoring: &Scoring) -> Timing {
+        let (_, gravity) = MASTER_GRAVITY
+            .iter()
+            .rev()
+            .find(|&&(level, _)| self.level >= level)
+            .copied()
+            .unwrap_or(MASTER_GRAVITY[0]);
+        let (_, are, lock_delay, line_clear_delay) = MASTER_DELAYS
+            .iter()
+            .rev()
+            .find(|&&(level, ..)| self.level >= level)
+            .copied()
+            .unwrap_or(MASTER_DELAYS[0]);
+
+        Timing {
+            gravity: gravity as f32 / 256.0,
+            lock_delay,
+            are,
+            line_clear_delay,
+        }
+    }
+
+    fn countdown(&self) -> f32 {
+        COUNTDOWN_SECONDS
+    }
+
+    fn on_piece_locked(&mut self, lines_cleared: u32, ctx: &ModeContext) {
+        let elapsed = ctx.stats.elapsed;
+        self.decay_grade(elapsed - self.last_lock);
+        self.last_lock = elapsed;
+
+        if lines_cleared > 0 {
+            self.award_grade_points(lines_cleared);
+        }
+        self.advance_level(lines_cleared, elapsed);
+    }
+
+    fn check_end(&mut self, ctx: &ModeContext) -> Option<Outcome> {
+        if self.level < MASTER_MAX_LEVEL {
+            return None;
+        }
+
+        self.grand_master =
+            self.grade == MASTER_GRADES.len() - 1 && ctx.stats.elapsed <= MASTER_GM_SECONDS;
+        Some(Outcome::Completed)
+    }
+
+    fn completion_title(&self) -> &'static str {
+        if self.grand_master {
+            "GRAND MASTER!"
+        } else {
+            "CONGRATULATIONS!"
+        }
+    }
+
+    fn hud(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![
+            ("LV", format!("{:03}/{:03}", self.level, self.next_stop())),
+            (
+                "SECTION",
+                format_time(ctx.stats.elapsed - self.section_start),
+            ),
+        ]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let mut results = vec![
+            ("GRADE", self.grade().to_owned()),
+            ("LEVEL", self.level.to_string()),
+            ("TIME", format_time(ctx.stats.elapsed)),
+            ("SCORE", ctx.scoring.score.to_string()),
+        ];
+        results.extend(
+            SECTION_LABELS
+                .iter()
+                .zip(&self.section_times)
+                .enumerate()
+                .map(|(index, (&label, &time))| {
+                    let value = match self.section_records.get(index) {
+                        Some(&best) if time < best => format!("{}  NEW BEST", format_time(time)),
+                        Some(&best) => format!("{}  BEST {}", format_time(time), format_time(best)),
+                        None => format_time(time),
+                    };
+                    (label, value)
+                }),
+        );
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::Board;
+    use crate::game::stats::GameStats;
+
+    fn lock(master: &mut Master, lines: u32, elapsed: f32) {
+        let board = Board::new();
+        let scoring = Scoring::new();
+        let mut stats = GameStats::new();
+        stats.elapsed = elapsed;
+
+        master.on_piece_locked(
+            lines,
+            &ModeContext {
+                board: &board,
+                scoring: &scoring,
+                stats: &stats,
+                hold: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_section_stop_needs_a_clear() {
+        let mut master = Master::new();
+        for _ in 0..120 {
+            lock(&mut master, 0, 1.0);
+        }
+        assert_eq!(master.level(), 99);
+        assert!(master.section_times().is_empty());
+
+        lock(&mut master, 2, 30.0);
+        assert_eq!(master.level(), 101);
+        assert_eq!(master.section_times(), &[30.0]);
+    }
+
+    #[test]
+    fn test_section_records_survive_reset() {
+        let finish_section = |master: &mut Master, elapsed: f32| {
+            master.level = 98;
+            lock(master, 2, elapsed);
+        };
+
+        let mut master = Master::new();
+        finish_section(&mut master, 40.0);
+        master.reset();
+        assert_eq!(master.section_records(), &[40.0]);
+        assert!(master.section_times().is_empty());
+
+        finish_section(&mut master, 45.0);
+        let first_section = |master: &Master| {
+            let board = Board::new();
+            let scoring = Scoring::new();
+            let stats = GameStats::new();
+            let ctx = ModeContext {
+                board: &board,
+                scoring: &scoring,
+                stats: &stats,
+                hold: None,
+            };
+            master.results(&ctx)[4].1.clone()
+        };
+        assert_eq!(first_section(&master), "0:45.000  BEST 0:40.000");
+        master.reset();
+        assert_eq!(master.section_records(), &[40.0]);
+
+        finish_section(&mut master, 35.0);
+        assert_eq!(first_section(&master), "0:35.000  NEW BEST");
+        master.reset();
+        assert_eq!(master.section_records(), &[35.0]);
+    }
+
+    #[test]
+    fn test_gravity_reaches_20g_and_delays_shrink() {
+        let mut master = Master::new();
+        let start = master.timing(&Scoring::new());
+        assert_eq!(start.gravity, 4.0 / 256.0);
+
+        master.level = 500;
+        assert_eq!(master.timing(&Scoring::new()).gravity, 20.0);
+
+        master.level = 950;
+        let late = master.timing(&Scoring::new());
+        assert!(late.lock_delay < start.lock_delay);
+        assert!(late.entry_delay(1) < start.entry_delay(1));
+    }
+
+    #[test]
+    fn test_grade_rises_with_clears_and_decays() {
+        let mut master = Master::new();
+        lock(&mut master, 4, 1.0);
+        lock(&mut master, 4, 2.0);
+        assert_eq!(master.grade(), "8");
+
+        lock(&mut master, 4, 3.0);
+        assert_eq!(master.grade_points, 50);
+        let interval = master.decay_interval();
+        lock(&mut master, 0, 3.0 + 10.5 * interval);
+        assert_eq!(master.grade_points, 40);
+    }
+
+    #[test]
+    fn test_reaching_999_completes() {
+        let board = Board::new();
+        let scoring = Scoring::new();
+        let stats = GameStats::new();
+        let ctx = ModeContext {
+            board: &board,
+            scoring: &scoring,
+            stats: &stats,
+            hold: None,
+        };
+
+        let mut master = Master::new();
+        master.level = 998;
+        assert_eq!(master.check_end(&ctx), None);
+
+        master.on_piece_locked(0, &ctx);
+        assert_eq!(master.level(), 998);
+        master.on_piece_locked(1, &ctx);
+        assert_eq!(master.check_end(&ctx), Some(Outcome::Completed));
+        assert_eq!(master.completion_title(), "CONGRATULATIONS!");
+    }
+}
@@ -0,0 +1,332 @@
+pub mod btype;
+pub mod cheese;
+pub mod endless;
+pub mod finesse;
+pub mod marathon;
+pub mod master;
+pub mod pc;
+pub mod puzzle;
+pub mod sprint;
+pub mod ultra;
+pub mod zen;
+
+pub use btype::BType;
+pub use cheese::Cheese;
+pub use endless::Endless;
+pub use finesse::FinesseTrainer;
+pub use marathon::Marathon;
+pub use master::Master;
+pub use pc::{Opener, PcTrainer};
+pub use puzzle::{Puzzle, PuzzleGoal, PuzzleMode};
+pub use sprint::Sprint;
+pub use ultra::Ultra;
+pub use zen::Zen;
+
+use super::board::Board;
+use super::finesse::FinesseFault;
+use super::scoring::Scoring;
+use super::stats::{format_time, GameStats};
+use super::timing::Timing;
+use crate::config::{CHEESE_ROWS, MARATHON_LINES};
+use crate::tetromino::{PieceBag, Randomizer, Tetromino, TetrominoType};
+use macroquad::rand::{RandGenerator, RandomRange};
+
+/// What a mode is played toward. Reaching it ends the game as `Completed`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Goal {
+    /// Play until topping out.
+    None,
+    /// Clear this many lines.
+    Lines(u32),
+    /// Survive for this many seconds.
+    Time(f32),
+    /// Reach this score.
+    Score(u32),
+}
+
+/// How a game ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// The goal or a mode-specific win condition was reached.
+    Completed,
+    /// A mode-specific lose condition was hit.
+    Failed,
+    ToppedOut,
+}
+
+/// Random numbers for a mode's garbage or targets: from a private
+/// generator once the game is seeded, so they repeat with the seed, and
+/// from the global one otherwise.
+#[derive(Default)]
+pub struct ModeRng(Option<RandGenerator>);
+
+impl ModeRng {
+    pub fn seeded(seed: u64) -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+        ModeRng(Some(rng))
+    }
+
+    pub fn gen_range<T: RandomRange>(&self, low: T, high: T) -> T {
+        match &self.0 {
+            Some(rng) => rng.gen_range(low, high),
+            None => macroquad::rand::gen_range(low, high),
+        }
+    }
+}
+
+/// Read-only view of a running game handed to mode hooks.
+pub struct ModeContext<'a> {
+    pub board: &'a Board,
+    pub scoring: &'a Scoring,
+    pub stats: &'a GameStats,
+    /// The piece in hold, if any.
+    pub hold: Option<TetrominoType>,
+}
+
+/// The rules that make one kind of game differ from another. `GameState`
+/// runs the falling-piece mechanics and asks its mode about everything else.
+pub trait GameMode: Send {
+    fn name(&self) -> &'static str;
+
+    /// Forget per-game state before a new game starts.
+    fn reset(&mut self) {}
+
+    /// Called before `setup_board` in a game started from `seed`. Modes
+    /// that draw anything at random should draw it from a `ModeRng` seeded
+    /// here, so the whole game repeats with the seed.
+    fn seed(&mut self, _seed: u64) {}
+
+    /// Prepare the starting board, e.g. with garbage or an authored layout.
+    fn setup_board(&mut self, _board: &mut Board) {}
+
+    fn create_randomizer(&mut self) -> Box<dyn Randomizer> {
+        Box::new(PieceBag::new())
+    }
+
+    /// Randomizer for a game whose piece order should repeat with `seed`.
+    /// Modes with a fixed order can ignore the seed.
+    fn create_seeded_randomizer(&mut self, seed: u64) -> Box<dyn Randomizer> {
+        Box::new(PieceBag::seeded(seed))
+    }
+
+    /// Level the game starts at.
+    fn starting_level(&self) -> u32 {
+        1
+    }
+
+    /// Scoring the game starts with, where a mode changes how levels go up.
+    fn create_scoring(&self) -> Scoring {
+        Scoring::with_start_level(self.starting_level())
+    }
+
+    /// Seconds between gravity steps.
+    fn drop_interval(&self, scoring: &Scoring) -> f32 {
+        scoring.get_drop_speed()
+    }
+
+    /// Gravity and lock and entry delays, by default from `drop_interval`.
+    fn timing(&self, scoring: &Scoring) -> Timing {
+        Timing::from_drop_interval(self.drop_interval(scoring))
+    }
+
+    fn goal(&self) -> Goal {
+        Goal::None
+    }
+
+    /// Seconds of "ready" countdown before pieces start moving.
+    fn countdown(&self) -> f32 {
+        0.0
+    }
+
+    /// Called whenever a piece enters play, including out of hold.
+    fn on_spawn(&mut self, _piece: &Tetromino) {}
+
+    /// Called as each piece locks, before lines are cleared, with the
+    /// finesse fault it earned if any. Returning `true` deals the same
+    /// piece again instead of the next one.
+    fn on_piece_placed(
+        &mut self,
+        _piece: &Tetromino,
+        _fault: Option<&FinesseFault>,
+        _board: &mut Board,
+    ) -> bool {
+        false
+    }
+
+    /// Called after each piece locks and its lines are cleared.
+    fn on_piece_locked(&mut self, _lines_cleared: u32, _ctx: &ModeContext) {}
+
+    /// Where the mode wants the current piece placed, drawn as an outline.
+    fn target(&self) -> Option<Tetromino> {
+        None
+    }
+
+    /// A shape the player should build, drawn faintly behind the stack.
+    fn guide(&self) -> Option<&Board> {
+        None
+    }
+
+    /// Whether the finesse overlay is shown without `--finesse`, for modes
+    /// that are about finesse.
+    fn shows_finesse(&self) -> bool {
+        false
+    }
+
+    /// Called when a lock, a spawn or garbage tops out. Returning `true`
+    /// means the mode made room on the board and play goes on instead of
+    /// ending.
+    fn recover_top_out(&mut self, _board: &mut Board) -> bool {
+        false
+    }
+
+    /// Whether pieces can be taken back. Undo replays the game from the
+    /// start, so only modes that deal the same board and pieces every time
+    /// should allow it.
+    fn allows_undo(&self) -> bool {
+        false
+    }
+
+    /// End conditions beyond the goal, checked after every lock and frame.
+    fn check_end(&mut self, _ctx: &ModeContext) -> Option<Outcome> {
+        None
+    }
+
+    /// Points added to the score when the game ends as `Completed`.
+    fn completion_bonus(&self, _ctx: &ModeContext) -> u32 {
+        0
+    }
+
+    /// Heading of the results screen after a `Completed` game.
+    fn completion_title(&self) -> &'static str {
+        "COMPLETE!"
+    }
+
+    /// Extra label/value pairs shown in the side panel while playing.
+    fn hud(&self, _ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Label/value pairs shown on the results screen.
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![
+            ("SCORE", ctx.scoring.score.to_string()),
+            ("LINES", ctx.scoring.lines_cleared.to_string()),
+            ("LEVEL", ctx.scoring.level.to_string()),
+            ("TIME", format_time(ctx.stats.elapsed)),
+        ]
+    }
+}
+
+impl Goal {
+    /// Whether the game has reached this goal.
+    pub fn is_reached(&self, ctx: &ModeContext) -> bool {
+        match *self {
+            Goal::None => false,
+            Goal::Lines(lines) => ctx.scoring.lines_cleared >= lines,
+            Goal::Time(seconds) => ctx.stats.elapsed >= seconds,
+            Goal::Score(score) => ctx.scoring.score >= score,
+        }
+    }
+}
+
+/// Settings a player can pick for the modes that take them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModeOptions {
+    /// Starting level.
+    pub level: Option<u32>,
+    /// B-Type starting garbage height, 0-5.
+    pub high: Option<u32>,
+    /// Garbage rows to dig through.
+    pub rows: Option<u32>,
+    /// Marathon line goal, 150 or 200.
+    pub lines: Option<u32>,
+    /// Level up by the guideline variable goal instead of every ten lines.
+    pub variable_goal: bool,
+    /// Puzzle to start from, counting from one.
+    pub puzzle: Option<u32>,
+    /// Puzzles to play instead of the bundled set.
+    pub puzzles: Option<Vec<Puzzle>>,
+    /// Opener to practise, counting from one.
+    pub opener: Option<u32>,
+    /// Openers to practise instead of the bundled set.
+    pub openers: Option<Vec<Opener>>,
+}
+
+/// Names accepted by `from_name`, in menu order.
+pub const MODE_NAMES: &[&str] = &[
+    "endless", "marathon", "sprint", "ultra", "btype", "cheese", "zen", "master", "puzzle",
+    "finesse", "pc",
+];
+
+/// Build a mode from its command-line name.
+pub fn from_name(name: &str, options: &ModeOptions) -> Option<Box<dyn GameMode>> {
+    match name {
+        "endless" => Some(Box::new(Endless)),
+        "marathon" => Some(Box::new(Marathon::new(
+            options.level.unwrap_or(1),
+            options.lines.unwrap_or(MARATHON_LINES),
+            options.variable_goal,
+        ))),
+        "sprint" => Some(Box::new(Sprint::new())),
+        "ultra" => Some(Box::new(Ultra::new())),
+        "btype" => Some(Box::new(BType::new(
+            options.level.unwrap_or(1),
+            options.high.unwrap_or(0),
+        ))),
+        "cheese" => Some(Box::new(Cheese::new(options.rows.unwrap_or(CHEESE_ROWS)))),
+        "zen" => Some(Box::new(Zen::new())),
+        "master" => Some(Box::new(Master::new())),
+        "puzzle" => {
+            let index = options.puzzle.unwrap_or(1).saturating_sub(1) as usize;
+            Some(Box::new(match &options.puzzles {
+                Some(puzzles) => PuzzleMode::new(puzzles.clone(), index),
+                None => PuzzleMode::bundled(index),
+            }))
+        }
+        "finesse" => Some(Box::new(FinesseTrainer::new())),
+        "pc" => {
+            let index = options.opener.unwrap_or(1).saturating_sub(1) as usize;
+            Some(Box::new(match &options.openers {
+                Some(openers) => PcTrainer::new(openers.clone(), index),
+                None => PcTrainer::bundled(index),
+            }))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_goal_is_reached() {
+        let board = Board::new();
+        let mut scoring = Scoring::new();
+        let mut stats = GameStats::new();
+        scoring.add_lines(4);
+        stats.elapsed = 30.0;
+
+        let ctx = ModeContext {
+            board: &board,
+            scoring: &scoring,
+            stats: &stats,
+            hold: None,
+        };
+        assert!(!Goal::None.is_reached(&ctx));
+        assert!(Goal::Lines(4).is_reached(&ctx));
+        assert!(!Goal::Lines(5).is_reached(&ctx));
+        assert!(Goal::Time(30.0).is_reached(&ctx));
+        assert!(!Goal::Score(scoring.score + 1).is_reached(&ctx));
+    }
+
+    #[test]
+    fn test_from_name() {
+        for name in MODE_NAMES {
+            let mode = from_name(name, &ModeOptions::default());
+            assert_eq!(mode.map(|mode| mode.name()), Some(*name));
+        }
+        assert!(from_name("nonsense", &ModeOptions::default()).is_none());
+    }
+}
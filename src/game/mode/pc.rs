@@ -0,0 +1,462 @@
+use super::puzzle::parse_row;
+use super::{GameMode, ModeContext, Outcome};
+use crate::ai::PcSolver;
+use crate::config::{PC_CHECK_POSITIONS, PC_HEIGHT, PC_SEARCH_POSITIONS, PC_SEED_ATTEMPTS};
+use crate::game::board::{Block, Board};
+use crate::game::scoring::Scoring;
+use crate::game::stats::format_time;
+use crate::game::timing::Timing;
+use crate::tetromino::{PieceBag, Randomizer, TetrominoType};
+
+/// The openers shipped with the game: the Perfect Clear Opener both ways
+/// round, then some practice drills.
+pub const BUNDLED_OPENERS: &str = include_str!("../../../assets/openers.txt");
+
+/// A shape to build at the start of a game that leads into a perfect clear.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Opener {
+    pub name: String,
+    /// Rows to build from top to bottom, resting on the floor of the board.
+    pub rows: Vec<Vec<Option<Block>>>,
+}
+
+/// Parse an opener file. Each opener starts with an `opener:` line naming
+/// it, followed by its rows, top first, with `.` for open cells and piece
+/// letters for the piece that fills each cell. Blank lines and lines
+/// starting with `#` are ignored.
+pub fn parse_openers(text: &str) -> Result<Vec<Opener>, String> {
+    let mut openers: Vec<Opener> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("line {}: {message}", number + 1);
+
+        if let Some(name) = line.strip_prefix("opener:") {
+            openers.push(Opener {
+                name: name.trim().to_owned(),
+                rows: Vec::new(),
+            });
+            continue;
+        }
+        let Some(opener) = openers.last_mut() else {
+            return Err(error("expected 'opener: <name>' first".to_owned()));
+        };
+
+        let row = parse_row(line).map_err(error)?;
+        if row.contains(&Some(Block::Garbage)) {
+            return Err(error("openers are built from pieces only".to_owned()));
+        }
+        opener.rows.push(row);
+    }
+
+    for opener in &openers {
+        if opener.rows.is_empty() {
+            return Err(format!("opener '{}' has no rows", opener.name));
+        }
+        if opener.rows.len() > PC_HEIGHT as usize {
+            return Err(format!(
+                "opener '{}' is taller than {PC_HEIGHT} rows",
+                opener.name
+            ));
+        }
+        check_pieces(opener)?;
+    }
+    Ok(openers)
+}
+
+/// Check that `opener` is built from whole pieces the first two bags can
+/// deal: each letter's cells split into groups of touching cells that are
+/// each one piece of that type, with at most two of any type.
+fn check_pieces(opener: &Opener) -> Result<(), String> {
+    for piece_type in TetrominoType::all() {
+        let own = Some(Block::Piece(piece_type));
+        let mut cells: Vec<(i32, i32)> = opener
+            .rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(move |&(_, &block)| block == own)
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect();
+
+        let mut pieces = 0;
+        while let Some(first) = cells.pop() {
+            let mut group = vec![first];
+            let mut next = 0;
+            while let Some(&(x, y)) = group.get(next) {
+                for neighbour in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                    if let Some(index) = cells.iter().position(|&cell| cell == neighbour) {
+                        group.push(cells.swap_remove(index));
+                    }
+                }
+                next += 1;
+            }
+
+            let letter = piece_type.letter();
+            if !is_piece(piece_type, &group) {
+                return Err(format!(
+                    "opener '{}': the {letter} cells are not whole {letter} pieces",
+                    opener.name
+                ));
+            }
+            pieces += 1;
+            if pieces > 2 {
+                return Err(format!(
+                    "opener '{}' needs more {letter} pieces than two bags deal",
+                    opener.name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `cells` are exactly one piece of `piece_type` in some rotation.
+fn is_piece(piece_type: TetrominoType, cells: &[(i32, i32)]) -> bool {
+    let normalised = |cells: &mut Vec<(i32, i32)>| {
+        let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        for cell in cells.iter_mut() {
+            *cell = (cell.0 - left, cell.1 - top);
+        }
+        cells.sort_unstable();
+    };
+
+    let mut shape = cells.to_vec();
+    normalised(&mut shape);
+    (0..4).any(|rotation| {
+        let mut rotated: Vec<(i32, i32)> = piece_type
+            .cells(rotation)
+            .iter()
+            .map(|&(dx, dy)| (dx as i32, dy as i32))
+            .collect();
+        normalised(&mut rotated);
+        rotated == shape
+    })
+}
+
+/// The first `count` pieces `PieceBag::seeded(seed)` deals.
+fn deal(seed: u64, count: usize) -> Vec<TetrominoType> {
+    let mut bag = PieceBag::seeded(seed);
+    (0..count).map(|_| bag.next_piece()).collect()
+}
+
+/// Perfect-clear opener practice with no gravity. The bag is seeded so the
+/// opener, drawn faintly on the board, can be built and then finished off
+/// into a perfect clear of the bottom `PC_HEIGHT` rows. After every lock
+/// the solver checks the clear is still possible with the pieces left, and
+/// the game is lost as soon as it is not. Pieces can be taken back, and a
+/// perfect clear moves on to a new bag.
+pub struct PcTrainer {
+    openers: Vec<Opener>,
+    index: usize,
+    /// Where the search for a solvable bag starts.
+    seed: u64,
+    /// A seed the search already started from and found nothing, so
+    /// resetting does not search again.
+    failed_from: Option<u64>,
+    /// The opener laid out on a board the size of the game's.
+    setup: Board,
+    /// The pieces the bag deals for one clear, plus one for hold.
+    sequence: Vec<TetrominoType>,
+    /// Lines cleared so far, which lower the top of the clear.
+    lines: i32,
+    solvable: bool,
+    solved: bool,
+}
+
+impl PcTrainer {
+    /// Practise opener `index` (from zero) of `openers`.
+    pub fn new(openers: Vec<Opener>, index: usize) -> Self {
+        assert!(
+            !openers.is_empty(),
+            "the PC trainer needs at least one opener"
+        );
+        PcTrainer {
+            index: index.min(openers.len() - 1),
+            openers,
+            seed: 0,
+            failed_from: None,
+            setup: Board::new(),
+            sequence: Vec::new(),
+            lines: 0,
+            solvable: true,
+            solved: false,
+        }
+    }
+
+    /// The bundled openers, starting at `index`.
+    pub fn bundled(index: usize) -> Self {
+        let openers = parse_openers(BUNDLED_OPENERS).expect("bundled openers parse");
+        Self::new(openers, index)
+    }
+
+    pub fn opener(&self) -> &Opener {
+        &self.openers[self.index]
+    }
+
+    fn solver(&self) -> PcSolver {
+        PcSolver::new((PC_HEIGHT - self.lines).max(0))
+    }
+}
+
+impl GameMode for PcTrainer {
+    fn name(&self) -> &'static str {
+        "pc"
+    }
+
+    fn reset(&mut self) {
+        if self.solved {
+            self.seed += 1;
+        }
+        self.lines = 0;
+        self.solvable = true;
+        self.solved = false;
+    }
+
+    fn setup_board(&mut self, board: &mut Board) {
+        let mut setup = board.clone();
+        let rows = &self.opener().rows;
+        // An opener wider than this game's board cannot be built on it.
+        let fits = rows.iter().all(|row| row.len() <= board.width() as usize);
+        let top = setup.height() - rows.len() as i32;
+        for (dy, row) in rows.iter().enumerate() {
+            for (x, &block) in row.iter().enumerate() {
+                setup.set_block(x as i32, top + dy as i32, block);
+            }
+        }
+        self.setup = setup;
+
+        // Find the first bag from `seed` on that can build the opener and
+        // clear, within a bounded search; if there is none, the game is
+        // lost from the start.
+        let count = (board.width() * PC_HEIGHT / 4) as usize + 1;
+        let solver = self.solver().with_budget(PC_SEARCH_POSITIONS);
+        let found = (fits && self.failed_from != Some(self.seed))
+            .then(|| {
+                (self.seed..self.seed + PC_SEED_ATTEMPTS).find(|&seed| {
+                    solver
+                        .solve_through(board, &deal(seed, count), None, &self.setup)
+                        .is_some()
+                })
+            })
+            .flatten();
+        if found.is_none() {
+            self.failed_from = Some(self.seed);
+        }
+        self.solvable = found.is_some();
+        self.seed = found.unwrap_or(self.seed);
+        self.sequence = deal(self.seed, count);
+    }
+
+    fn create_randomizer(&mut self) -> Box<dyn Randomizer> {
+        Box::new(PieceBag::seeded(self.seed))
+    }
+
+    fn create_seeded_randomizer(&mut self, _seed: u64) -> Box<dyn Randomizer> {
+        self.create_randomizer()
+    }
+
+    fn timing(&self, _scoring: &Scoring) -> Timing {
+        Timing {
+            gravity: 0.0,
+            lock_delay: u32::MAX,
+            are: 0,
+            line_clear_delay: 0,
+        }
+    }
+
+    fn allows_undo(&self) -> bool {
+        true
+    }
+
+    fn on_piece_locked(&mut self, lines_cleared: u32, ctx: &ModeContext) {
+        self.lines += lines_cleared as i32;
+        if lines_cleared > 0 && ctx.board.is_empty() {
+            self.solved = true;
+            return;
+        }
+
+        // Everything placed or held has been dealt; the rest comes in order.
+        let dealt = ctx.stats.pieces_placed as usize + ctx.hold.is_some() as usize;
+        let queue = self.sequence.get(dealt..).unwrap_or_default();
+        // A check that runs out of budget gives the player the benefit of
+        // the doubt rather than holding up the frame.
+        let setup = (self.lines == 0).then_some(&self.setup);
+        self.solvable = !self
+            .solver()
+            .with_budget(PC_CHECK_POSITIONS)
+            .rules_out(ctx.board, queue, ctx.hold, setup);
+    }
+
+    fn guide(&self) -> Option<&Board> {
+        (self.lines == 0).then_some(&self.setup)
+    }
+
+    fn check_end(&mut self, _ctx: &ModeContext) -> Option<Outcome> {
+        if self.solved {
+            return Some(Outcome::Completed);
+        }
+        (!self.solvable).then_some(Outcome::Failed)
+    }
+
+    fn completion_title(&self) -> &'static str {
+        "PERFECT CLEAR!"
+    }
+
+    fn hud(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![
+            ("OPENER", self.opener().name.to_uppercase()),
+            (
+                "PIECES",
+                format!(
+                    "{}/{}",
+                    ctx.stats.pieces_placed,
+                    self.sequence.len().saturating_sub(1)
+                ),
+            ),
+        ]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![
+            ("OPENER", self.opener().name.to_uppercase()),
+            ("PIECES", ctx.stats.pieces_placed.to_string()),
+            ("LINES", self.lines.to_string()),
+            ("TIME", format_time(ctx.stats.elapsed)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ruleset::Ruleset;
+    use crate::game::GameState;
+
+    fn trainer(index: usize) -> GameState {
+        GameState::with_mode(Ruleset::standard(), Box::new(PcTrainer::bundled(index)))
+    }
+
+    #[test]
+    fn test_bundled_openers_parse_and_are_solvable() {
+        let openers = parse_openers(BUNDLED_OPENERS).unwrap();
+        assert!(openers.len() >= 4);
+        assert_eq!(openers[0].name, "Perfect Clear Opener");
+        assert_eq!(openers[1].name, "Perfect Clear Opener (Mirror)");
+        let mirrored: Vec<Vec<Option<Block>>> = openers[0]
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .rev()
+                    .map(|block| match block {
+                        Some(Block::Piece(TetrominoType::L)) => {
+                            Some(Block::Piece(TetrominoType::J))
+                        }
+                        Some(Block::Piece(TetrominoType::Z)) => {
+                            Some(Block::Piece(TetrominoType::S))
+                        }
+                        &block => block,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(openers[1].rows, mirrored);
+
+        for index in 0..openers.len() {
+            let mut mode = PcTrainer::bundled(index);
+            mode.reset();
+            mode.setup_board(&mut Board::new());
+            assert!(mode.solvable, "{}", mode.opener().name);
+            assert_eq!(mode.sequence.len(), 11);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_openers("..........\n").is_err());
+        assert!(parse_openers("opener: A\n").is_err());
+        assert!(parse_openers("opener: A\nXX........\n").is_err());
+        assert!(parse_openers("opener: A\nI\nI\nI\nI\nI\n").is_err());
+        assert!(parse_openers("opener: A\nII?.\n").is_err());
+        // Cells that are not one whole piece, or more pieces than two bags.
+        assert!(parse_openers("opener: A\nTTTT......\n").is_err());
+        assert!(parse_openers("opener: A\nOOOOOO....\nOOOOOO....\n").is_err());
+        assert!(parse_openers("opener: A\nOO.OO.OO..\nOO.OO.OO..\n").is_err());
+        assert!(parse_openers("opener: A\nOO.OO.....\nOO.OO.....\n").is_ok());
+    }
+
+    #[test]
+    fn test_opener_wider_than_the_board_fails() {
+        let mut mode = PcTrainer::bundled(0);
+        mode.reset();
+        mode.setup_board(&mut Board::with_ruleset(&Ruleset::new(8, 20)));
+        assert!(!mode.solvable);
+    }
+
+    #[test]
+    fn test_failed_search_is_not_repeated() {
+        let openers = parse_openers("opener: A\nOO.OO.....\nOO.OO.....\n").unwrap();
+        let mut mode = PcTrainer::new(openers, 0);
+        mode.reset();
+        mode.setup_board(&mut Board::new());
+        assert!(!mode.solvable);
+        assert_eq!(mode.failed_from, Some(0));
+
+        mode.reset();
+        mode.setup_board(&mut Board::new());
+        assert!(!mode.solvable);
+    }
+
+    #[test]
+    fn test_following_the_solution_clears_the_board() {
+        let mut mode = PcTrainer::bundled(0);
+        mode.reset();
+        mode.setup_board(&mut Board::new());
+        let solution = PcSolver::new(PC_HEIGHT)
+            .solve_through(&Board::new(), &mode.sequence, None, &mode.setup)
+            .unwrap();
+
+        let mut state = trainer(0);
+        for piece in solution {
+            if piece.piece_type != state.current_piece.piece_type {
+                assert!(state.hold());
+            }
+            state.current_piece = piece;
+            state.hard_drop();
+        }
+        assert_eq!(state.outcome, Some(Outcome::Completed));
+        assert!(state.board.is_empty());
+
+        // A clear moves on to the next solvable bag.
+        mode.solved = true;
+        mode.reset();
+        mode.setup_board(&mut Board::new());
+        state.reset();
+        assert_eq!(state.current_piece.piece_type, mode.sequence[0]);
+        assert_eq!(state.next_piece.piece_type, mode.sequence[1]);
+    }
+
+    #[test]
+    fn test_breaking_the_opener_fails_and_undo_takes_it_back() {
+        let mut state = trainer(0);
+        assert!(state.mode().guide().is_some());
+
+        // Anything dropped against the left wall lands on cells the
+        // opener's I and O are meant for.
+        while state.try_move(-1, 0) {}
+        state.hard_drop();
+        assert_eq!(state.outcome, Some(Outcome::Failed));
+
+        assert!(state.undo());
+        assert_eq!(state.outcome, None);
+        assert!(state.board.is_empty());
+    }
+}
@@ -0,0 +1,413 @@
+use super::{GameMode, ModeContext, Outcome};
+use crate::game::board::{Block, Board};
+use crate::game::scoring::{Scoring, Spin};
+use crate::game::timing::Timing;
+use crate::tetromino::{PieceSequence, Randomizer, TetrominoType};
+
+/// The puzzles shipped with the game.
+pub const BUNDLED_PUZZLES: &str = include_str!("../../../assets/puzzles.txt");
+
+/// What a puzzle asks the player to do with its pieces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PuzzleGoal {
+    /// Clear at least this many lines in total.
+    Lines(u32),
+    /// Clear this many lines at once with a T-spin.
+    TSpin(u32),
+    /// Clear lines until the board is completely empty.
+    PerfectClear,
+}
+
+impl PuzzleGoal {
+    pub fn describe(&self) -> String {
+        match *self {
+            PuzzleGoal::Lines(1) => "CLEAR 1 LINE".to_owned(),
+            PuzzleGoal::Lines(lines) => format!("CLEAR {lines} LINES"),
+            PuzzleGoal::TSpin(1) => "T-SPIN SINGLE".to_owned(),
+            PuzzleGoal::TSpin(2) => "T-SPIN DOUBLE".to_owned(),
+            PuzzleGoal::TSpin(_) => "T-SPIN TRIPLE".to_owned(),
+            PuzzleGoal::PerfectClear => "PERFECT CLEAR".to_owned(),
+        }
+    }
+}
+
+/// An authored starting board, piece sequence and goal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub name: String,
+    pub goal: PuzzleGoal,
+    pub pieces: Vec<TetrominoType>,
+    /// Starting rows from top to bottom, resting on the floor of the board.
+    pub rows: Vec<Vec<Option<Block>>>,
+}
+
+/// Parse a puzzle file. Each puzzle starts with a `puzzle:` line naming
+/// it, followed by `goal:` (`lines N`, `tspin N` or `perfect-clear`),
+/// `pieces:` (letters in play order) and its board rows, top first, with
+/// `.` for empty cells, `X` for garbage and piece letters for colored
+/// blocks. Every row of a puzzle must be the same width, which is the
+/// board width it is played on. Blank lines and lines starting with `#`
+/// are ignored.
+pub fn parse_puzzles(text: &str) -> Result<Vec<Puzzle>, String> {
+    let mut puzzles: Vec<PuzzleDraft> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("line {}: {message}", number + 1);
+
+        if let Some(name) = line.strip_prefix("puzzle:") {
+            puzzles.push(PuzzleDraft {
+                name: name.trim().to_owned(),
+                goal: None,
+                pieces: Vec::new(),
+                rows: Vec::new(),
+            });
+            continue;
+        }
+        let Some(puzzle) = puzzles.last_mut() else {
+            return Err(error("expected 'puzzle: <name>' first".to_owned()));
+        };
+
+        if let Some(goal) = line.strip_prefix("goal:") {
+            puzzle.goal = Some(parse_goal(goal.trim()).map_err(error)?);
+        } else if let Some(pieces) = line.strip_prefix("pieces:") {
+            puzzle.pieces = pieces
+                .trim()
+                .chars()
+                .map(|letter| {
+                    TetrominoType::from_letter(letter)
+                        .ok_or_else(|| error(format!("unknown piece '{letter}'")))
+                })
+                .collect::<Result<_, _>>()?;
+        } else {
+            let row = parse_row(line).map_err(error)?;
+            if puzzle
+                .rows
+                .first()
+                .is_some_and(|first| first.len() != row.len())
+            {
+                return Err(error("rows must all be the same width".to_owned()));
+            }
+            puzzle.rows.push(row);
+        }
+    }
+
+    puzzles
+        .into_iter()
+        .map(|puzzle| {
+            if puzzle.pieces.is_empty() {
+                return Err(format!("puzzle '{}' has no pieces", puzzle.name));
+            }
+            let Some(goal) = puzzle.goal else {
+                return Err(format!("puzzle '{}' has no goal", puzzle.name));
+            };
+            Ok(Puzzle {
+                name: puzzle.name,
+                goal,
+                pieces: puzzle.pieces,
+                rows: puzzle.rows,
+            })
+        })
+        .collect()
+}
+
+/// A puzzle partway through parsing, before its goal is known.
+struct PuzzleDraft {
+    name: String,
+    goal: Option<PuzzleGoal>,
+    pieces: Vec<TetrominoType>,
+    rows: Vec<Vec<Option<Block>>>,
+}
+
+/// Parse one board row: `.` for an empty cell, `X` for garbage and piece
+/// letters for colored blocks.
+pub fn parse_row(line: &str) -> Result<Vec<Option<Block>>, String> {
+    line.chars()
+        .map(|cell| match cell {
+            '.' => Ok(None),
+            'X' => Ok(Some(Block::Garbage)),
+            _ => TetrominoType::from_letter(cell)
+                .map(|piece| Some(Block::Piece(piece)))
+                .ok_or_else(|| format!("unknown cell '{cell}'")),
+        })
+        .collect()
+}
+
+fn parse_goal(goal: &str) -> Result<PuzzleGoal, String> {
+    let (kind, count) = goal.split_once(' ').unwrap_or((goal, ""));
+    let count = || {
+        count
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|&count| count > 0)
+            .ok_or_else(|| format!("bad goal count in '{goal}'"))
+    };
+
+    match kind {
+        "lines" => Ok(PuzzleGoal::Lines(count()?)),
+        "tspin" => match count()? {
+            lines @ 1..=3 => Ok(PuzzleGoal::TSpin(lines)),
+            _ => Err(format!("a T-spin clears at most 3 lines in '{goal}'")),
+        },
+        "perfect-clear" => Ok(PuzzleGoal::PerfectClear),
+        _ => Err(format!("unknown goal '{goal}'")),
+    }
+}
+
+/// Work through a set of puzzles one at a time. There is no gravity, so
+/// pieces only lock when dropped; pieces can be taken back, and solving a
+/// puzzle moves on to the next one. A puzzle authored for another board
+/// width fails straight away.
+pub struct PuzzleMode {
+    puzzles: Vec<Puzzle>,
+    index: usize,
+    /// Whether the puzzle's rows are as wide as the board.
+    fits: bool,
+    achieved: bool,
+    solved: bool,
+}
+
+impl PuzzleMode {
+    /// Start at puzzle `index` (from zero) of `puzzles`.
+    pub fn new(puzzles: Vec<Puzzle>, index: usize) -> Self {
+        assert!(!puzzles.is_empty(), "puzzle mode needs at least one puzzle");
+        PuzzleMode {
+            index: index.min(puzzles.len() - 1),
+            puzzles,
+            fits: true,
+            achieved: false,
+            solved: false,
+        }
+    }
+
+    /// The bundled puzzles, starting at `index`.
+    pub fn bundled(index: usize) -> Self {
+        let puzzles = parse_puzzles(BUNDLED_PUZZLES).expect("bundled puzzles parse");
+        Self::new(puzzles, index)
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzles[self.index]
+    }
+}
+
+impl GameMode for PuzzleMode {
+    fn name(&self) -> &'static str {
+        "puzzle"
+    }
+
+    fn reset(&mut self) {
+        if self.solved {
+            self.index = (self.index + 1) % self.puzzles.len();
+        }
+        self.achieved = false;
+        self.solved = false;
+    }
+
+    fn setup_board(&mut self, board: &mut Board) {
+        let rows = &self.puzzles[self.index].rows;
+        self.fits = rows.iter().all(|row| row.len() == board.width() as usize);
+        if !self.fits {
+            return;
+        }
+        let top = board.height() - rows.len() as i32;
+
+        for (dy, row) in rows.iter().enumerate() {
+            for (x, &block) in row.iter().enumerate() {
+                board.set_block(x as i32, top + dy as i32, block);
+            }
+        }
+    }
+
+    fn create_randomizer(&mut self) -> Box<dyn Randomizer> {
+        Box::new(PieceSequence::new(self.puzzle().pieces.clone()))
+    }
+
+    fn create_seeded_randomizer(&mut self, _seed: u64) -> Box<dyn Randomizer> {
+        self.create_randomizer()
+    }
+
+    fn timing(&self, _scoring: &Scoring) -> Timing {
+        Timing {
+            gravity: 0.0,
+            lock_delay: u32::MAX,
+            are: 0,
+            line_clear_delay: 0,
+        }
+    }
+
+    fn allows_undo(&self) -> bool {
+        true
+    }
+
+    fn on_piece_locked(&mut self, lines_cleared: u32, ctx: &ModeContext) {
+        self.achieved |= match self.puzzle().goal {
+            PuzzleGoal::Lines(lines) => ctx.scoring.lines_cleared >= lines,
+            PuzzleGoal::TSpin(lines) => ctx
+                .scoring
+                .last_clear
+                .is_some_and(|clear| clear.spin == Spin::Full && clear.lines == lines),
+            PuzzleGoal::PerfectClear => lines_cleared > 0 && ctx.board.is_empty(),
+        };
+    }
+
+    fn check_end(&mut self, ctx: &ModeContext) -> Option<Outcome> {
+        if !self.fits {
+            return Some(Outcome::Failed);
+        }
+        if self.achieved {
+            self.solved = true;
+            return Some(Outcome::Completed);
+        }
+        (ctx.stats.pieces_placed >= self.puzzle().pieces.len() as u32).then_some(Outcome::Failed)
+    }
+
+    fn completion_title(&self) -> &'static str {
+        "SOLVED!"
+    }
+
+    fn hud(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let pieces = self.puzzle().pieces.len() as u32;
+        vec![
+            (
+                "PUZZLE",
+                format!("{}/{}", self.index + 1, self.puzzles.len()),
+            ),
+            ("GOAL", self.puzzle().goal.describe()),
+            (
+                "PIECES LEFT",
+                pieces.saturating_sub(ctx.stats.pieces_placed).to_string(),
+            ),
+        ]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        let puzzle = self.puzzle();
+        vec![
+            (
+                "PUZZLE",
+                format!("{}/{}", self.index + 1, self.puzzles.len()),
+            ),
+            ("NAME", puzzle.name.to_uppercase()),
+            ("GOAL", puzzle.goal.describe()),
+            (
+                "PIECES",
+                format!("{}/{}", ctx.stats.pieces_placed, puzzle.pieces.len()),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ruleset::Ruleset;
+    use crate::game::GameState;
+
+    fn first_puzzle() -> GameState {
+        GameState::with_mode(Ruleset::standard(), Box::new(PuzzleMode::bundled(0)))
+    }
+
+    #[test]
+    fn test_bundled_puzzles_parse() {
+        let puzzles = parse_puzzles(BUNDLED_PUZZLES).unwrap();
+        assert!(puzzles.len() >= 20);
+        for puzzle in &puzzles {
+            assert!(
+                puzzle.rows.iter().all(|row| row.len() == 10),
+                "{}",
+                puzzle.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_puzzle() {
+        let text = "# comment\npuzzle: Demo\ngoal: tspin 2\npieces: tO\n\nX.T.......\n";
+        let puzzles = parse_puzzles(text).unwrap();
+
+        assert_eq!(puzzles.len(), 1);
+        assert_eq!(puzzles[0].name, "Demo");
+        assert_eq!(puzzles[0].goal, PuzzleGoal::TSpin(2));
+        assert_eq!(puzzles[0].pieces, [TetrominoType::T, TetrominoType::O]);
+        assert_eq!(puzzles[0].rows[0][0], Some(Block::Garbage));
+        assert_eq!(puzzles[0].rows[0][2], Some(Block::Piece(TetrominoType::T)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_puzzles("goal: lines 1\n").is_err());
+        assert!(parse_puzzles("puzzle: A\ngoal: lines 1\npieces: Q\n").is_err());
+        assert!(parse_puzzles("puzzle: A\ngoal: lines\npieces: I\n").is_err());
+        assert!(parse_puzzles("puzzle: A\npieces: I\n").is_err());
+        assert!(parse_puzzles("puzzle: A\ngoal: lines 1\npieces: I\nXX?\n").is_err());
+        assert!(parse_puzzles("puzzle: A\ngoal: tspin 4\npieces: T\n").is_err());
+        assert!(parse_puzzles("puzzle: A\ngoal: lines 1\npieces: I\nXX.\nX.\n").is_err());
+    }
+
+    #[test]
+    fn test_puzzle_for_another_width_fails() {
+        let puzzles = parse_puzzles("puzzle: A\ngoal: lines 1\npieces: I\nXXXXXXXX....\n");
+        let mode = PuzzleMode::new(puzzles.unwrap(), 0);
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(mode));
+        assert!(state.board.is_empty());
+
+        state.update(0.1);
+        assert_eq!(state.outcome, Some(Outcome::Failed));
+    }
+
+    #[test]
+    fn test_solving_moves_on_to_next_puzzle() {
+        let mut state = first_puzzle();
+        assert_eq!(state.board.garbage_rows(), 4);
+
+        state.try_rotate();
+        while state.try_move(1, 0) {}
+        state.hard_drop();
+        assert_eq!(state.outcome, Some(Outcome::Completed));
+        assert!(state.board.is_empty());
+        assert!(!state.can_undo());
+
+        state.reset();
+        let count = parse_puzzles(BUNDLED_PUZZLES).unwrap().len();
+        assert_eq!(state.hud()[0], ("PUZZLE", format!("2/{count}")));
+    }
+
+    #[test]
+    fn test_running_out_of_pieces_fails_and_undo_takes_it_back() {
+        let mut state = first_puzzle();
+        state.hard_drop();
+        assert_eq!(state.outcome, Some(Outcome::Failed));
+        assert!(state.can_undo());
+
+        assert!(state.undo());
+        assert_eq!(state.outcome, None);
+        assert_eq!(state.stats.pieces_placed, 0);
+        assert_eq!(state.current_piece.piece_type, TetrominoType::I);
+        assert_eq!(
+            state.board.rows().iter().filter(|&&row| row != 0).count(),
+            4
+        );
+        assert!(!state.undo());
+    }
+
+    #[test]
+    fn test_undo_replays_holds() {
+        // Corner Stone deals O then L.
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(PuzzleMode::bundled(5)));
+        assert!(state.hold());
+        assert_eq!(state.current_piece.piece_type, TetrominoType::L);
+        state.hard_drop();
+        state.hard_drop();
+        assert!(state.undo());
+
+        assert_eq!(state.stats.pieces_placed, 1);
+        assert_eq!(state.hold_piece, Some(TetrominoType::O));
+        assert_eq!(state.current_piece.piece_type, TetrominoType::O);
+        assert!(state.can_hold());
+    }
+}
@@ -0,0 +1,113 @@
+use super::{GameMode, Goal, ModeContext};
+use crate::config::{COUNTDOWN_SECONDS, INITIAL_DROP_SPEED, SPRINT_LINES, SPRINT_SPLIT_LINES};
+use crate::game::scoring::Scoring;
+use crate::game::stats::format_time;
+
+/// Clear 40 lines as fast as possible. Gravity stays at level one so only
+/// the player's speed matters, and the time is split every ten lines.
+pub struct Sprint {
+    splits: Vec<f32>,
+}
+
+impl Sprint {
+    pub fn new() -> Self {
+        Sprint { splits: Vec::new() }
+    }
+
+    /// Elapsed time when each block of ten lines was completed.
+    pub fn splits(&self) -> &[f32] {
+        &self.splits
+    }
+}
+
+impl Default for Sprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameMode for Sprint {
+    fn name(&self) -> &'static str {
+        "sprint"
+    }
+
+    fn reset(&mut self) {
+        self.splits.clear();
+    }
+
+    fn drop_interval(&self, _scoring: &Scoring) -> f32 {
+        INITIAL_DROP_SPEED
+    }
+
+    fn goal(&self) -> Goal {
+        Goal::Lines(SPRINT_LINES)
+    }
+
+    fn countdown(&self) -> f32 {
+        COUNTDOWN_SECONDS
+    }
+
+    fn on_piece_locked(&mut self, _lines_cleared: u32, ctx: &ModeContext) {
+        let lines = ctx.scoring.lines_cleared.min(SPRINT_LINES);
+        while (self.splits.len() as u32 + 1) * SPRINT_SPLIT_LINES <= lines {
+            self.splits.push(ctx.stats.elapsed);
+        }
+    }
+
+    fn hud(&self, _ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        match self.splits.last() {
+            Some(&split) => vec![("SPLIT", format_time(split))],
+            None => Vec::new(),
+        }
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        const SPLIT_LABELS: [&str; 4] = ["10 LINES", "20 LINES", "30 LINES", "40 LINES"];
+
+        let mut results = vec![
+            ("TIME", format_time(ctx.stats.elapsed)),
+            ("PIECES", ctx.stats.pieces_placed.to_string()),
+            ("PPS", format!("{:.2}", ctx.stats.pieces_per_second())),
+            ("FAULTS", ctx.stats.finesse_faults.to_string()),
+        ];
+        results.extend(
+            SPLIT_LABELS
+                .iter()
+                .zip(&self.splits)
+                .map(|(&label, &split)| (label, format_time(split))),
+        );
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::Board;
+    use crate::game::stats::GameStats;
+
+    #[test]
+    fn test_splits_every_ten_lines() {
+        let mut sprint = Sprint::new();
+        let board = Board::new();
+        let mut scoring = Scoring::new();
+        let mut stats = GameStats::new();
+
+        for (lines, elapsed) in [(4, 5.0), (4, 9.0), (4, 14.0), (4, 30.0), (4, 41.0)] {
+            scoring.add_lines(lines);
+            stats.elapsed = elapsed;
+            let ctx = ModeContext {
+                board: &board,
+                scoring: &scoring,
+                stats: &stats,
+                hold: None,
+            };
+            sprint.on_piece_locked(lines, &ctx);
+        }
+
+        assert_eq!(sprint.splits(), &[14.0, 41.0]);
+
+        sprint.reset();
+        assert!(sprint.splits().is_empty());
+    }
+}
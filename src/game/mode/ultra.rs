@@ -0,0 +1,111 @@
+use super::{GameMode, Goal, ModeContext};
+use crate::config::{COUNTDOWN_SECONDS, INITIAL_DROP_SPEED, ULTRA_SECONDS};
+use crate::game::scoring::{Scoring, Spin};
+use crate::game::stats::format_time;
+
+/// Two-minute score attack. Gravity stays at level one, so the score comes
+/// from clearing efficiently: tetrises, T-spins, combos and back-to-backs.
+pub struct Ultra {
+    tetrises: u32,
+    tspins: u32,
+    max_combo: u32,
+}
+
+impl Ultra {
+    pub fn new() -> Self {
+        Ultra {
+            tetrises: 0,
+            tspins: 0,
+            max_combo: 0,
+        }
+    }
+}
+
+impl Default for Ultra {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameMode for Ultra {
+    fn name(&self) -> &'static str {
+        "ultra"
+    }
+
+    fn reset(&mut self) {
+        *self = Ultra::new();
+    }
+
+    fn drop_interval(&self, _scoring: &Scoring) -> f32 {
+        INITIAL_DROP_SPEED
+    }
+
+    fn goal(&self) -> Goal {
+        Goal::Time(ULTRA_SECONDS)
+    }
+
+    fn countdown(&self) -> f32 {
+        COUNTDOWN_SECONDS
+    }
+
+    fn on_piece_locked(&mut self, _lines_cleared: u32, ctx: &ModeContext) {
+        let Some(clear) = ctx.scoring.last_clear else {
+            return;
+        };
+
+        if clear.lines >= 4 {
+            self.tetrises += 1;
+        }
+        if clear.spin != Spin::None && clear.lines > 0 {
+            self.tspins += 1;
+        }
+        self.max_combo = self.max_combo.max(clear.combo);
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![
+            ("SCORE", ctx.scoring.score.to_string()),
+            ("LINES", ctx.scoring.lines_cleared.to_string()),
+            ("PIECES", ctx.stats.pieces_placed.to_string()),
+            ("TETRISES", self.tetrises.to_string()),
+            ("T-SPINS", self.tspins.to_string()),
+            ("MAX COMBO", self.max_combo.to_string()),
+            ("TIME", format_time(ctx.stats.elapsed.min(ULTRA_SECONDS))),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::Board;
+    use crate::game::stats::GameStats;
+
+    #[test]
+    fn test_counts_attack_clears() {
+        let mut ultra = Ultra::new();
+        let board = Board::new();
+        let mut scoring = Scoring::new();
+        let stats = GameStats::new();
+
+        for (lines, spin) in [
+            (4, Spin::None),
+            (2, Spin::Full),
+            (1, Spin::None),
+            (0, Spin::None),
+        ] {
+            scoring.score_lock(lines, spin);
+            let ctx = ModeContext {
+                board: &board,
+                scoring: &scoring,
+                stats: &stats,
+                hold: None,
+            };
+            ultra.on_piece_locked(lines, &ctx);
+        }
+
+        assert_eq!(ultra.tetrises, 1);
+        assert_eq!(ultra.tspins, 1);
+        assert_eq!(ultra.max_combo, 2);
+    }
+}
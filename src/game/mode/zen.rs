@@ -0,0 +1,88 @@
+use super::{GameMode, ModeContext};
+use crate::config::INITIAL_DROP_SPEED;
+use crate::game::board::Board;
+use crate::game::scoring::Scoring;
+use crate::game::stats::format_time;
+
+/// Relaxed endless play for warming up and trying out setups. Gravity stays
+/// at level one and topping out never ends the game: the top half of the
+/// field is wiped instead, while score, lines and time carry on.
+pub struct Zen {
+    top_outs: u32,
+}
+
+impl Zen {
+    pub fn new() -> Self {
+        Zen { top_outs: 0 }
+    }
+}
+
+impl Default for Zen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameMode for Zen {
+    fn name(&self) -> &'static str {
+        "zen"
+    }
+
+    fn reset(&mut self) {
+        *self = Zen::new();
+    }
+
+    fn drop_interval(&self, _scoring: &Scoring) -> f32 {
+        INITIAL_DROP_SPEED
+    }
+
+    fn recover_top_out(&mut self, board: &mut Board) -> bool {
+        board.clear_above(board.height() / 2);
+        self.top_outs += 1;
+        true
+    }
+
+    fn hud(&self, _ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![("TOP OUTS", self.top_outs.to_string())]
+    }
+
+    fn results(&self, ctx: &ModeContext) -> Vec<(&'static str, String)> {
+        vec![
+            ("SCORE", ctx.scoring.score.to_string()),
+            ("LINES", ctx.scoring.lines_cleared.to_string()),
+            ("TIME", format_time(ctx.stats.elapsed)),
+            ("TOP OUTS", self.top_outs.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ruleset::Ruleset;
+    use crate::game::GameState;
+
+    #[test]
+    fn test_top_out_clears_top_rows_instead_of_ending() {
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(Zen::new()));
+        let height = state.board.height();
+        state.add_garbage(height as u32 - 1, 0);
+
+        state.hard_drop();
+        assert!(!state.game_over);
+        assert_eq!(state.stats.pieces_placed, 1);
+        assert!(!state.board.has_block(5, height / 2 - 1));
+        assert!(state.board.has_block(5, height / 2));
+        assert_eq!(state.hud(), vec![("TOP OUTS", "1".to_owned())]);
+    }
+
+    #[test]
+    fn test_garbage_overflow_is_recovered() {
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(Zen::new()));
+        let total = (state.board.height() + crate::config::BUFFER_HEIGHT) as u32;
+
+        state.add_garbage(total + 1, 0);
+        assert!(!state.game_over);
+        assert!(state.board.is_valid_position(&state.current_piece));
+    }
+}
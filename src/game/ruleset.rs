@@ -0,0 +1,155 @@
+use crate::config::{BOARD_HEIGHT, BOARD_WIDTH, FADE_SECONDS};
+use std::str::FromStr;
+
+/// Widest board a row bitmask can hold.
+pub const MAX_BOARD_WIDTH: i32 = 32;
+
+/// How long locked blocks stay on screen, for memory training.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StackVisibility {
+    Visible,
+    /// Blocks vanish as soon as they lock.
+    Invisible,
+    /// Blocks vanish this many seconds after they lock.
+    Fading(f32),
+}
+
+/// Board geometry and stack display chosen at runtime, so the same game
+/// can be played on narrow, tall or oversized fields, or from memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ruleset {
+    pub board_width: i32,
+    pub board_height: i32,
+    pub stack: StackVisibility,
+    /// Briefly outline a hidden stack after each line clear.
+    pub stack_outline: bool,
+}
+
+impl Ruleset {
+    pub fn new(board_width: i32, board_height: i32) -> Self {
+        assert!(
+            (4..=MAX_BOARD_WIDTH).contains(&board_width),
+            "board width must be between 4 and {MAX_BOARD_WIDTH}"
+        );
+        assert!(board_height >= 4, "board height must be at least 4");
+
+        Ruleset {
+            board_width,
+            board_height,
+            stack: StackVisibility::Visible,
+            stack_outline: false,
+        }
+    }
+
+    /// The same ruleset with locked blocks shown as `stack` says.
+    pub fn with_stack(mut self, stack: StackVisibility, outline: bool) -> Self {
+        self.stack = stack;
+        self.stack_outline = outline;
+        self
+    }
+
+    /// The classic 10x20 field.
+    pub fn standard() -> Self {
+        Self::new(BOARD_WIDTH, BOARD_HEIGHT)
+    }
+
+    pub fn four_wide() -> Self {
+        Self::new(4, BOARD_HEIGHT)
+    }
+
+    pub fn six_wide() -> Self {
+        Self::new(6, BOARD_HEIGHT)
+    }
+
+    pub fn tall() -> Self {
+        Self::new(BOARD_WIDTH, 40)
+    }
+
+    pub fn big() -> Self {
+        Self::new(20, 40)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Parses a preset name (`standard`, `4-wide`, `6-wide`, `tall`, `big`) or
+/// explicit dimensions such as `10x40`.
+impl FromStr for Ruleset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => return Ok(Self::standard()),
+            "4-wide" => return Ok(Self::four_wide()),
+            "6-wide" => return Ok(Self::six_wide()),
+            "tall" => return Ok(Self::tall()),
+            "big" => return Ok(Self::big()),
+            _ => {}
+        }
+
+        let (width, height) = s
+            .split_once('x')
+            .ok_or_else(|| format!("unknown board '{s}', expected WIDTHxHEIGHT"))?;
+        let width: i32 = width
+            .parse()
+            .map_err(|_| format!("bad board width '{width}'"))?;
+        let height: i32 = height
+            .parse()
+            .map_err(|_| format!("bad board height '{height}'"))?;
+
+        if !(4..=MAX_BOARD_WIDTH).contains(&width) || height < 4 {
+            return Err(format!("board {width}x{height} is out of range"));
+        }
+        Ok(Self::new(width, height))
+    }
+}
+
+/// Parses `visible`, `invisible`, `fade` (after `FADE_SECONDS`) or
+/// `fade:SECONDS`.
+impl FromStr for StackVisibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "visible" => return Ok(StackVisibility::Visible),
+            "invisible" => return Ok(StackVisibility::Invisible),
+            "fade" => return Ok(StackVisibility::Fading(FADE_SECONDS)),
+            _ => {}
+        }
+
+        let seconds = s
+            .strip_prefix("fade:")
+            .ok_or_else(|| format!("unknown stack '{s}', expected visible, invisible or fade"))?;
+        match seconds.parse::<f32>() {
+            Ok(seconds) if seconds >= 0.0 => Ok(StackVisibility::Fading(seconds)),
+            _ => Err(format!("bad fade time '{seconds}'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_presets_and_dimensions() {
+        assert_eq!("standard".parse(), Ok(Ruleset::standard()));
+        assert_eq!("4-wide".parse(), Ok(Ruleset::new(4, BOARD_HEIGHT)));
+        assert_eq!("20x40".parse(), Ok(Ruleset::big()));
+        assert!("10by20".parse::<Ruleset>().is_err());
+        assert!("40x20".parse::<Ruleset>().is_err());
+    }
+
+    #[test]
+    fn test_parse_stack_visibility() {
+        assert_eq!("invisible".parse(), Ok(StackVisibility::Invisible));
+        assert_eq!("fade".parse(), Ok(StackVisibility::Fading(FADE_SECONDS)));
+        assert_eq!("fade:2.5".parse(), Ok(StackVisibility::Fading(2.5)));
+        assert!("fade:soon".parse::<StackVisibility>().is_err());
+        assert!("hidden".parse::<StackVisibility>().is_err());
+    }
+}
@@ -1,56 +1,256 @@
 use crate::config::*;
 
+/// Whether a lock counted as a T-spin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Spin {
+    None,
+    Mini,
+    Full,
+}
+
+/// A scored lock that cleared lines or was a spin, for display and stats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clear {
+    pub lines: u32,
+    pub spin: Spin,
+    pub back_to_back: bool,
+    /// Consecutive clearing locks before this one.
+    pub combo: u32,
+}
+
+impl Clear {
+    /// Name of the clear, e.g. "B2B T-SPIN DOUBLE".
+    pub fn label(&self) -> String {
+        let spin = match self.spin {
+            Spin::None => "",
+            Spin::Mini => "T-SPIN MINI",
+            Spin::Full => "T-SPIN",
+        };
+        let lines = match self.lines {
+            0 => "",
+            1 => "SINGLE",
+            2 => "DOUBLE",
+            3 => "TRIPLE",
+            _ => "TETRIS",
+        };
+        let prefix = if self.back_to_back { "B2B" } else { "" };
+
+        [prefix, spin, lines]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Tetrises and line-clearing spins keep a back-to-back chain going.
+    pub fn is_difficult(&self) -> bool {
+        self.lines >= 4 || (self.lines > 0 && self.spin != Spin::None)
+    }
+}
+
+/// How many lines it takes to go up a level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LevelProgression {
+    /// The level follows total lines cleared, this many per level.
+    Fixed(u32),
+    /// Guideline variable goal: level N takes 5 × N lines to clear,
+    /// counted from the starting level.
+    Variable,
+}
+
+impl LevelProgression {
+    /// Level reached after clearing `lines` lines from `start_level`, and
+    /// the lines still needed for the level after it.
+    fn level_after(&self, start_level: u32, lines: u32) -> (u32, u32) {
+        match *self {
+            LevelProgression::Fixed(per_level) => {
+                let level = (lines / per_level + 1).max(start_level);
+                (level, level * per_level - lines)
+            }
+            LevelProgression::Variable => {
+                let mut level = start_level;
+                let mut remaining = lines;
+                while remaining >= VARIABLE_GOAL_LINES * level {
+                    remaining -= VARIABLE_GOAL_LINES * level;
+                    level += 1;
+                }
+                (level, VARIABLE_GOAL_LINES * level - remaining)
+            }
+        }
+    }
+}
+
+impl Default for LevelProgression {
+    fn default() -> Self {
+        LevelProgression::Fixed(LINES_PER_LEVEL)
+    }
+}
+
 pub struct Scoring {
     pub score: u32,
     pub lines_cleared: u32,
     pub level: u32,
+    /// The level never drops below this one.
+    pub start_level: u32,
+    /// The level never rises above this one.
+    pub max_level: u32,
+    pub progression: LevelProgression,
+    /// The most recent lock, if it cleared lines or was a spin.
+    pub last_clear: Option<Clear>,
+    /// Consecutive clearing locks so far, or `None` after a lock that
+    /// cleared nothing.
+    combo: Option<u32>,
+    back_to_back: bool,
 }
 
 impl Scoring {
     pub fn new() -> Self {
+        Self::with_start_level(1)
+    }
+
+    /// Scoring for a game that starts at `level` instead of level one.
+    pub fn with_start_level(level: u32) -> Self {
+        let level = level.max(1);
         Scoring {
             score: 0,
             lines_cleared: 0,
-            level: 1,
+            level,
+            start_level: level,
+            max_level: u32::MAX,
+            progression: LevelProgression::default(),
+            last_clear: None,
+            combo: None,
+            back_to_back: false,
         }
     }
 
+    /// The same scoring, levelling up by `progression` instead.
+    pub fn with_progression(mut self, progression: LevelProgression) -> Self {
+        self.progression = progression;
+        self
+    }
+
+    /// The same scoring, with the level capped at `max_level`.
+    pub fn with_level_cap(mut self, max_level: u32) -> Self {
+        self.max_level = max_level.max(self.start_level);
+        self
+    }
+
     /// Add score based on lines cleared
     pub fn add_lines(&mut self, lines: u32) {
         self.lines_cleared += lines;
+        self.score += Self::line_points(lines, Spin::None) * self.level;
+        self.update_level();
+    }
 
-        let line_score = match lines {
-            1 => SCORE_SINGLE,
-            2 => SCORE_DOUBLE,
-            3 => SCORE_TRIPLE,
-            4 => SCORE_TETRIS,
-            _ => 0,
+    /// Score a locked piece, including T-spin, combo and back-to-back
+    /// bonuses, and return what it counted as.
+    pub fn score_lock(&mut self, lines: u32, spin: Spin) -> Option<Clear> {
+        if lines == 0 {
+            self.combo = None;
+            self.last_clear = None;
+            if spin == Spin::None {
+                return None;
+            }
+        }
+
+        let combo = match lines {
+            0 => 0,
+            _ => self.combo.map_or(0, |combo| combo + 1),
+        };
+        let mut clear = Clear {
+            lines,
+            spin,
+            back_to_back: false,
+            combo,
         };
 
-        self.score += line_score * self.level;
+        let mut points = Self::line_points(lines, spin);
+        if lines > 0 {
+            clear.back_to_back = self.back_to_back && clear.is_difficult();
+            if clear.back_to_back {
+                points = points * 3 / 2;
+            }
+            self.back_to_back = clear.is_difficult();
+            self.combo = Some(combo);
+        }
+
+        self.score += (points + SCORE_COMBO * combo) * self.level;
+        self.lines_cleared += lines;
         self.update_level();
+
+        self.last_clear = Some(clear);
+        Some(clear)
+    }
+
+    fn line_points(lines: u32, spin: Spin) -> u32 {
+        match (spin, lines) {
+            (Spin::None, 1) => SCORE_SINGLE,
+            (Spin::None, 2) => SCORE_DOUBLE,
+            (Spin::None, 3) => SCORE_TRIPLE,
+            (Spin::None, 4) => SCORE_TETRIS,
+            (Spin::Mini, 0) => SCORE_TSPIN_MINI,
+            (Spin::Mini, 1) => SCORE_TSPIN_MINI_SINGLE,
+            (Spin::Mini, _) => SCORE_TSPIN_MINI_DOUBLE,
+            (Spin::Full, 0) => SCORE_TSPIN,
+            (Spin::Full, 1) => SCORE_TSPIN_SINGLE,
+            (Spin::Full, 2) => SCORE_TSPIN_DOUBLE,
+            (Spin::Full, _) => SCORE_TSPIN_TRIPLE,
+            _ => 0,
+        }
     }
 
     /// Update level based on lines cleared
     fn update_level(&mut self) {
-        let new_level = self.lines_cleared / LINES_PER_LEVEL + 1;
+        let (new_level, _) = self
+            .progression
+            .level_after(self.start_level, self.lines_cleared);
+        let new_level = new_level.min(self.max_level);
         if new_level != self.level {
             self.level = new_level;
         }
     }
 
+    /// Lines left to clear before the next level, or `None` at the cap.
+    pub fn lines_to_next_level(&self) -> Option<u32> {
+        if self.level >= self.max_level {
+            return None;
+        }
+        let (_, remaining) = self
+            .progression
+            .level_after(self.start_level, self.lines_cleared);
+        Some(remaining)
+    }
+
+    /// Clearing locks in a row so far, counting the first; zero after a
+    /// lock that cleared nothing.
+    pub fn combo_count(&self) -> u32 {
+        self.combo.map_or(0, |combo| combo + 1)
+    }
+
+    /// Whether the last clear was a tetris or T-spin, so the next one
+    /// would be back-to-back.
+    pub fn is_back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+
     /// Get current drop speed based on level
     pub fn get_drop_speed(&self) -> f32 {
-        (INITIAL_DROP_SPEED - (self.level - 1) as f32 * SPEED_INCREASE_PER_LEVEL)
+        Self::drop_speed_for_level(self.level)
+    }
+
+    /// Seconds between gravity steps at a given level
+    pub fn drop_speed_for_level(level: u32) -> f32 {
+        (INITIAL_DROP_SPEED - level.saturating_sub(1) as f32 * SPEED_INCREASE_PER_LEVEL)
             .max(MIN_DROP_SPEED)
     }
 
     /// Reset scoring
     #[allow(dead_code)]
     pub fn reset(&mut self) {
-        self.score = 0;
-        self.lines_cleared = 0;
-        self.level = 1;
+        *self = Scoring::with_start_level(self.start_level)
+            .with_progression(self.progression)
+            .with_level_cap(self.max_level);
     }
 }
 
@@ -113,4 +313,96 @@ mod tests {
         
         assert!(new_speed < initial_speed);
     }
+
+    #[test]
+    fn test_tspin_scores() {
+        let mut scoring = Scoring::new();
+        let clear = scoring.score_lock(2, Spin::Full).unwrap();
+
+        assert_eq!(clear.label(), "T-SPIN DOUBLE");
+        assert_eq!(scoring.score, SCORE_TSPIN_DOUBLE);
+        assert_eq!(scoring.lines_cleared, 2);
+
+        scoring.score_lock(0, Spin::None);
+        assert_eq!(scoring.last_clear, None);
+        assert_eq!(
+            scoring.score_lock(0, Spin::Mini).map(|clear| clear.label()),
+            Some("T-SPIN MINI".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_back_to_back_bonus() {
+        let mut scoring = Scoring::new();
+        scoring.score_lock(4, Spin::None);
+        scoring.score_lock(0, Spin::None);
+        let before = scoring.score;
+
+        let clear = scoring.score_lock(4, Spin::None).unwrap();
+        assert!(clear.back_to_back);
+        assert_eq!(clear.label(), "B2B TETRIS");
+        assert_eq!(scoring.score - before, SCORE_TETRIS * 3 / 2);
+
+        scoring.score_lock(1, Spin::None);
+        let clear = scoring.score_lock(4, Spin::None).unwrap();
+        assert!(!clear.back_to_back);
+    }
+
+    #[test]
+    fn test_combo_bonus() {
+        let mut scoring = Scoring::new();
+        scoring.score_lock(1, Spin::None);
+        let before = scoring.score;
+
+        let clear = scoring.score_lock(1, Spin::None).unwrap();
+        assert_eq!(clear.combo, 1);
+        assert_eq!(scoring.score - before, SCORE_SINGLE + SCORE_COMBO);
+
+        scoring.score_lock(0, Spin::None);
+        assert_eq!(scoring.score_lock(1, Spin::None).unwrap().combo, 0);
+    }
+
+    #[test]
+    fn test_start_level() {
+        let mut scoring = Scoring::with_start_level(5);
+        assert_eq!(scoring.level, 5);
+
+        scoring.add_lines(4);
+        assert_eq!(scoring.level, 5);
+        assert_eq!(scoring.score, SCORE_TETRIS * 5);
+
+        scoring.add_lines(50);
+        assert_eq!(scoring.level, 6);
+    }
+
+    #[test]
+    fn test_variable_goal_progression() {
+        let mut scoring = Scoring::new().with_progression(LevelProgression::Variable);
+        assert_eq!(scoring.lines_to_next_level(), Some(5));
+
+        scoring.add_lines(4);
+        scoring.add_lines(1);
+        assert_eq!(scoring.level, 2);
+        assert_eq!(scoring.lines_to_next_level(), Some(10));
+
+        scoring.add_lines(10);
+        assert_eq!(scoring.level, 3);
+
+        let mut scoring = Scoring::with_start_level(4).with_progression(LevelProgression::Variable);
+        scoring.add_lines(19);
+        assert_eq!(scoring.level, 4);
+        scoring.add_lines(1);
+        assert_eq!(scoring.level, 5);
+    }
+
+    #[test]
+    fn test_level_cap() {
+        let mut scoring = Scoring::new().with_level_cap(3);
+        scoring.add_lines(50);
+        assert_eq!(scoring.level, 3);
+        assert_eq!(scoring.lines_to_next_level(), None);
+
+        scoring.reset();
+        assert_eq!(scoring.max_level, 3);
+    }
 }
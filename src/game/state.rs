@@ -1,73 +1,278 @@
-use super::board::Board;
+use super::board::{Block, Board};
+use super::finesse::{FinesseFault, FinesseTable};
+use super::mode::{Endless, GameMode, ModeContext, Outcome};
+use super::ruleset::{Ruleset, StackVisibility};
 use super::scoring::Scoring;
-use crate::tetromino::{PieceBag, Tetromino};
+use super::stats::GameStats;
+use crate::ai::Input;
+use crate::config::{FRAMES_PER_SECOND, STACK_OUTLINE_SECONDS};
+use crate::tetromino::{Randomizer, Tetromino, TetrominoType};
 
 pub struct GameState {
     pub board: Board,
     pub current_piece: Tetromino,
     pub next_piece: Tetromino,
+    /// Piece set aside with `hold`, if any.
+    pub hold_piece: Option<TetrominoType>,
     pub scoring: Scoring,
+    pub stats: GameStats,
     pub game_over: bool,
+    /// Why the game ended, once `game_over` is set.
+    pub outcome: Option<Outcome>,
     pub show_help: bool,
-    piece_bag: PieceBag,
-    drop_timer: f32,
+    pub ruleset: Ruleset,
+    mode: Box<dyn GameMode>,
+    randomizer: Box<dyn Randomizer>,
+    /// Seconds of play not yet run through `step_frame`.
+    frame_time: f32,
+    /// Fraction of a row the piece has fallen toward the next one.
+    gravity_progress: f32,
+    /// Frames the piece has spent grounded since it last fell a row.
+    lock_frames: u32,
+    /// Frames left before the next piece becomes playable.
+    entry_delay: u32,
+    countdown: f32,
+    /// Whether the current piece came out of a hold, which allows only one
+    /// per piece.
+    hold_used: bool,
+    /// Kick index of the last successful action, if it was a rotation.
+    last_kick: Option<usize>,
+    /// Game time of the most recent line clear.
+    last_line_clear: Option<f32>,
+    /// Seed for the piece order, kept across resets, if the game was
+    /// started with one.
+    seed: Option<u64>,
+    /// Every locked piece so far with the kick that got it there and
+    /// whether it was held first, so the game can be replayed for undo.
+    placements: Vec<(Tetromino, Option<usize>, bool)>,
+    /// Key presses on the current piece, for the finesse check.
+    piece_inputs: Vec<Input>,
+    /// Where in `piece_inputs` the latest left or right tap is, for DAS
+    /// charged by holding that key to replace.
+    last_tap: Option<usize>,
+    /// Built the first time a piece locks after a key press.
+    finesse: Option<FinesseTable>,
+    last_fault: Option<FinesseFault>,
 }
 
 impl GameState {
     pub fn new() -> Self {
-        let mut piece_bag = PieceBag::new();
-        let current_piece_type = piece_bag.next_piece();
-        let next_piece_type = piece_bag.next_piece();
-
-        GameState {
-            board: Board::new(),
-            current_piece: Tetromino::new(current_piece_type),
-            next_piece: Tetromino::new(next_piece_type),
-            scoring: Scoring::new(),
+        Self::with_ruleset(Ruleset::standard())
+    }
+
+    pub fn with_ruleset(ruleset: Ruleset) -> Self {
+        Self::with_mode(ruleset, Box::new(Endless))
+    }
+
+    pub fn with_mode(ruleset: Ruleset, mode: Box<dyn GameMode>) -> Self {
+        Self::build(ruleset, mode, None)
+    }
+
+    /// A game whose piece order depends only on `seed`, for reproducible
+    /// runs. Resetting replays the same order.
+    pub fn with_seed(ruleset: Ruleset, mode: Box<dyn GameMode>, seed: u64) -> Self {
+        Self::build(ruleset, mode, Some(seed))
+    }
+
+    fn build(ruleset: Ruleset, mut mode: Box<dyn GameMode>, seed: Option<u64>) -> Self {
+        mode.reset();
+        if let Some(seed) = seed {
+            mode.seed(seed);
+        }
+        let mut board = Board::with_ruleset(&ruleset);
+        mode.setup_board(&mut board);
+
+        let mut randomizer = match seed {
+            Some(seed) => mode.create_seeded_randomizer(seed),
+            None => mode.create_randomizer(),
+        };
+        let current_piece_type = randomizer.next_piece();
+        let next_piece_type = randomizer.next_piece();
+        let width = ruleset.board_width;
+        let countdown = mode.countdown();
+        let scoring = mode.create_scoring();
+
+        let mut state = GameState {
+            board,
+            current_piece: Tetromino::spawn(current_piece_type, width),
+            next_piece: Tetromino::spawn(next_piece_type, width),
+            hold_piece: None,
+            scoring,
+            stats: GameStats::new(),
             game_over: false,
+            outcome: None,
             show_help: true,
-            piece_bag,
-            drop_timer: 0.0,
+            ruleset,
+            mode,
+            randomizer,
+            frame_time: 0.0,
+            gravity_progress: 0.0,
+            lock_frames: 0,
+            entry_delay: 0,
+            countdown,
+            hold_used: false,
+            last_kick: None,
+            last_line_clear: None,
+            seed,
+            placements: Vec::new(),
+            piece_inputs: Vec::new(),
+            last_tap: None,
+            finesse: None,
+            last_fault: None,
+        };
+        state.mode.on_spawn(&state.current_piece);
+        state
+    }
+
+    pub fn mode(&self) -> &dyn GameMode {
+        self.mode.as_ref()
+    }
+
+    /// Label/value pairs for the results screen, as chosen by the mode.
+    pub fn results(&self) -> Vec<(&'static str, String)> {
+        self.mode.results(&ModeContext {
+            board: &self.board,
+            scoring: &self.scoring,
+            stats: &self.stats,
+            hold: self.hold_piece,
+        })
+    }
+
+    /// Mode-specific label/value pairs for the side panel.
+    pub fn hud(&self) -> Vec<(&'static str, String)> {
+        self.mode.hud(&ModeContext {
+            board: &self.board,
+            scoring: &self.scoring,
+            stats: &self.stats,
+            hold: self.hold_piece,
+        })
+    }
+
+    /// Seconds left before play starts; zero once the game is running.
+    pub fn countdown(&self) -> f32 {
+        self.countdown
+    }
+
+    pub fn is_counting_down(&self) -> bool {
+        self.countdown > 0.0
+    }
+
+    /// Whether the game is waiting out the delay before the next piece.
+    pub fn is_entry_delay(&self) -> bool {
+        self.entry_delay > 0
+    }
+
+    /// Whether the locked block at `(x, y)` is shown under the ruleset's
+    /// stack visibility. Garbage always is, and the whole stack is revealed
+    /// once the game ends.
+    pub fn is_block_visible(&self, x: i32, y: i32) -> bool {
+        match (self.board.get_block(x, y), self.ruleset.stack) {
+            (None, _) => false,
+            (Some(Block::Garbage), _) | (_, StackVisibility::Visible) => true,
+            _ if self.game_over => true,
+            (_, StackVisibility::Invisible) => false,
+            (_, StackVisibility::Fading(seconds)) => {
+                self.stats.elapsed - self.board.locked_at(x, y) < seconds
+            }
         }
     }
 
+    /// Whether a hidden stack should be outlined after a recent line clear.
+    pub fn is_outline_shown(&self) -> bool {
+        self.ruleset.stack_outline
+            && self
+                .last_line_clear
+                .is_some_and(|time| self.stats.elapsed - time < STACK_OUTLINE_SECONDS)
+    }
+
     pub fn update(&mut self, dt: f32) {
         if self.game_over {
             return;
         }
 
-        self.drop_timer += dt;
-        let drop_speed = self.scoring.get_drop_speed();
+        if self.is_counting_down() {
+            self.countdown = (self.countdown - dt).max(0.0);
+            return;
+        }
+
+        self.stats.elapsed += dt;
+        if let Some(outcome) = self.check_end() {
+            self.finish(outcome);
+            return;
+        }
+
+        let frame = 1.0 / FRAMES_PER_SECOND;
+        self.frame_time += dt;
+        while self.frame_time >= frame && !self.game_over {
+            self.frame_time -= frame;
+            self.step_frame();
+        }
+    }
 
-        if self.drop_timer >= drop_speed {
+    /// Advance one frame of entry delay, gravity and lock delay.
+    fn step_frame(&mut self) {
+        if self.entry_delay > 0 {
+            self.entry_delay -= 1;
+            return;
+        }
+
+        let timing = self.mode.timing(&self.scoring);
+        self.gravity_progress += timing.gravity;
+        while self.gravity_progress >= 1.0 {
+            self.gravity_progress -= 1.0;
             if !self.try_move(0, 1) {
+                self.gravity_progress = 0.0;
+            }
+        }
+
+        if self.is_grounded() {
+            self.lock_frames += 1;
+            if self.lock_frames >= timing.lock_delay {
                 self.lock_piece();
             }
-            self.drop_timer = 0.0;
         }
     }
 
+    fn is_grounded(&self) -> bool {
+        let mut below = self.current_piece;
+        below.move_by(0, 1);
+        !self.board.is_valid_position(&below)
+    }
+
     pub fn try_move(&mut self, dx: i32, dy: i32) -> bool {
-        let mut new_piece = self.current_piece.clone();
+        let mut new_piece = self.current_piece;
         new_piece.move_by(dx, dy);
 
         if self.board.is_valid_position(&new_piece) {
             self.current_piece = new_piece;
+            self.last_kick = None;
+            if dy > 0 {
+                self.lock_frames = 0;
+            }
             true
         } else {
             false
         }
     }
 
+    /// Rotate clockwise, trying the SRS wall kicks in order.
     pub fn try_rotate(&mut self) -> bool {
-        let mut new_piece = self.current_piece.clone();
-        new_piece.rotate();
+        self.try_rotate_towards(true)
+    }
 
-        if self.board.is_valid_position(&new_piece) {
-            self.current_piece = new_piece;
-            true
-        } else {
-            false
+    /// Rotate counter-clockwise, trying the SRS wall kicks in order.
+    pub fn try_rotate_ccw(&mut self) -> bool {
+        self.try_rotate_towards(false)
+    }
+
+    fn try_rotate_towards(&mut self, clockwise: bool) -> bool {
+        match self.board.try_rotate(&self.current_piece, clockwise) {
+            Some((rotated, kick)) => {
+                self.current_piece = rotated;
+                self.last_kick = Some(kick);
+                true
+            }
+            None => false,
         }
     }
 
@@ -78,30 +283,249 @@ impl GameState {
         self.lock_piece();
     }
 
+    /// Whether `hold` is allowed: once per piece, and not again until the
+    /// piece it brought out has locked.
+    pub fn can_hold(&self) -> bool {
+        !self.hold_used
+    }
+
+    /// Swap the current piece with the held one, or with the next piece if
+    /// nothing is held yet. The piece comes back at the spawn position.
+    pub fn hold(&mut self) -> bool {
+        if !self.can_hold() {
+            return false;
+        }
+
+        let held = self.current_piece.piece_type;
+        match self.hold_piece.replace(held) {
+            Some(piece_type) => self.spawn(Tetromino::spawn(piece_type, self.board.width())),
+            None => self.spawn_next_piece(),
+        }
+        self.hold_used = true;
+        true
+    }
+
+    /// Apply one move-generator input to the current piece, returning
+    /// whether it did anything. Held inputs repeat until the piece stops.
+    pub fn apply_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Left => self.try_move(-1, 0),
+            Input::Right => self.try_move(1, 0),
+            Input::DasLeft => self.repeat_move(-1, 0),
+            Input::DasRight => self.repeat_move(1, 0),
+            Input::SoftDrop => self.repeat_move(0, 1),
+            Input::RotateCw => self.try_rotate(),
+            Input::RotateCcw => self.try_rotate_ccw(),
+            Input::HardDrop => {
+                self.hard_drop();
+                true
+            }
+        }
+    }
+
+    /// A key press by the player: apply `input` and count it toward the
+    /// piece's finesse, whether or not it moved anything. DAS replaces the
+    /// latest tap the same way, since holding that key is what charged it,
+    /// even if other keys were pressed in between.
+    pub fn press(&mut self, input: Input) -> bool {
+        let tap = match input {
+            Input::DasLeft => Some(Input::Left),
+            Input::DasRight => Some(Input::Right),
+            _ => None,
+        };
+        let held = self
+            .last_tap
+            .filter(|&index| tap == Some(self.piece_inputs[index]));
+        match held {
+            Some(index) => self.piece_inputs[index] = input,
+            None => {
+                if matches!(input, Input::Left | Input::Right) {
+                    self.last_tap = Some(self.piece_inputs.len());
+                }
+                self.piece_inputs.push(input);
+            }
+        }
+        self.apply_input(input)
+    }
+
+    /// Key presses on the current piece so far.
+    pub fn piece_inputs(&self) -> &[Input] {
+        &self.piece_inputs
+    }
+
+    /// The most recent piece locked with more presses than it needed.
+    pub fn last_finesse_fault(&self) -> Option<&FinesseFault> {
+        self.last_fault.as_ref()
+    }
+
+    /// Count a fault if the piece about to lock took more presses than
+    /// the finesse table allows, returning whether it did. Pieces nobody
+    /// pressed a key for, such as the bot's, are skipped.
+    fn check_finesse(&mut self) -> bool {
+        if self.piece_inputs.is_empty() {
+            return false;
+        }
+        let table = self
+            .finesse
+            .get_or_insert_with(|| FinesseTable::new(&self.ruleset));
+        let time = self.stats.elapsed;
+        let Some(fault) = table.check(&self.current_piece, &self.piece_inputs, time) else {
+            return false;
+        };
+        self.stats.finesse_faults += 1;
+        self.last_fault = Some(fault);
+        true
+    }
+
+    fn repeat_move(&mut self, dx: i32, dy: i32) -> bool {
+        let mut moved = false;
+        while self.try_move(dx, dy) {
+            moved = true;
+        }
+        moved
+    }
+
     fn lock_piece(&mut self) {
-        self.board.place_piece(&self.current_piece);
+        let faulted = self.check_finesse();
+        self.placements
+            .push((self.current_piece, self.last_kick, self.hold_used));
+        let spin = self.board.detect_spin(&self.current_piece, self.last_kick);
+        self.board
+            .place_piece_at(&self.current_piece, self.stats.elapsed);
+        let fault = self.last_fault.as_ref().filter(|_| faulted);
+        let retry = self
+            .mode
+            .on_piece_placed(&self.current_piece, fault, &mut self.board);
 
-        if self.current_piece.y <= 0 {
-            self.game_over = true;
+        if self.current_piece.y <= 0 && !self.mode.recover_top_out(&mut self.board) {
+            self.finish(Outcome::ToppedOut);
             return;
         }
 
         let lines_cleared = self.board.clear_lines();
-        self.scoring.add_lines(lines_cleared);
+        if lines_cleared > 0 {
+            self.last_line_clear = Some(self.stats.elapsed);
+        }
+        self.entry_delay = self.mode.timing(&self.scoring).entry_delay(lines_cleared);
+        self.scoring.score_lock(lines_cleared, spin);
+        self.stats.pieces_placed += 1;
+
+        self.mode.on_piece_locked(
+            lines_cleared,
+            &ModeContext {
+                board: &self.board,
+                scoring: &self.scoring,
+                stats: &self.stats,
+                hold: self.hold_piece,
+            },
+        );
+        if let Some(outcome) = self.check_end() {
+            self.finish(outcome);
+            return;
+        }
 
-        self.spawn_next_piece();
+        if retry {
+            self.hold_used = false;
+            let piece_type = self.current_piece.piece_type;
+            self.spawn(Tetromino::spawn(piece_type, self.board.width()));
+        } else {
+            self.spawn_next_piece();
+        }
+    }
+
+    /// Ask the mode whether its goal or any other end condition was hit.
+    fn check_end(&mut self) -> Option<Outcome> {
+        let ctx = ModeContext {
+            board: &self.board,
+            scoring: &self.scoring,
+            stats: &self.stats,
+            hold: self.hold_piece,
+        };
+
+        if self.mode.goal().is_reached(&ctx) {
+            return Some(Outcome::Completed);
+        }
+        self.mode.check_end(&ctx)
+    }
+
+    fn finish(&mut self, outcome: Outcome) {
+        if outcome == Outcome::Completed {
+            self.scoring.score += self.mode.completion_bonus(&ModeContext {
+                board: &self.board,
+                scoring: &self.scoring,
+                stats: &self.stats,
+                hold: self.hold_piece,
+            });
+        }
+
+        self.game_over = true;
+        self.outcome = Some(outcome);
     }
 
     fn spawn_next_piece(&mut self) {
-        self.current_piece = self.next_piece.clone();
-        self.current_piece.reset_position();
+        self.hold_used = false;
+        self.spawn(self.next_piece);
+
+        let next_type = self.randomizer.next_piece();
+        self.next_piece = Tetromino::spawn(next_type, self.board.width());
+    }
+
+    fn spawn(&mut self, piece: Tetromino) {
+        self.last_kick = None;
+        self.piece_inputs.clear();
+        self.last_tap = None;
+        self.gravity_progress = 0.0;
+        self.lock_frames = 0;
+        self.current_piece = piece;
+        self.current_piece.reset_position(self.board.width());
+
+        // Block out: the new piece has nowhere to appear.
+        if !self.board.is_valid_position(&self.current_piece)
+            && !self.mode.recover_top_out(&mut self.board)
+        {
+            self.finish(Outcome::ToppedOut);
+            return;
+        }
+        self.mode.on_spawn(&self.current_piece);
+    }
 
-        let next_type = self.piece_bag.next_piece();
-        self.next_piece = Tetromino::new(next_type);
+    /// Push garbage rows up under the stack; see `Board::add_garbage`.
+    pub fn add_garbage(&mut self, rows: u32, hole_column: i32) {
+        let fits = self.board.add_garbage(rows, hole_column);
+        self.settle_after_garbage(rows, fits);
+    }
+
+    /// Push garbage rows with a hole column chosen per row; see
+    /// `Board::add_garbage_with`.
+    pub fn add_garbage_with(&mut self, rows: u32, hole_for_row: impl FnMut(u32) -> i32) {
+        let fits = self.board.add_garbage_with(rows, hole_for_row);
+        self.settle_after_garbage(rows, fits);
+    }
+
+    /// Top out if the stack left the buffer zone, otherwise lift the falling
+    /// piece clear of the raised stack.
+    fn settle_after_garbage(&mut self, rows: u32, fits: bool) {
+        if !fits && !self.mode.recover_top_out(&mut self.board) {
+            self.finish(Outcome::ToppedOut);
+            return;
+        }
+
+        for _ in 0..rows {
+            if self.board.is_valid_position(&self.current_piece) {
+                return;
+            }
+            self.current_piece.y -= 1;
+        }
+
+        if !self.board.is_valid_position(&self.current_piece)
+            && !self.mode.recover_top_out(&mut self.board)
+        {
+            self.finish(Outcome::ToppedOut);
+        }
     }
 
     pub fn get_ghost_piece(&self) -> Tetromino {
-        let mut ghost = self.current_piece.clone();
+        let mut ghost = self.current_piece;
         while self.board.is_valid_position(&ghost) {
             ghost.y += 1;
         }
@@ -117,8 +541,45 @@ impl GameState {
         self.show_help = false;
     }
 
+    /// Whether `undo` would take back a piece: the mode must allow it, a
+    /// piece must have locked, and the game must not already be won.
+    pub fn can_undo(&self) -> bool {
+        self.mode.allows_undo()
+            && !self.placements.is_empty()
+            && self.outcome != Some(Outcome::Completed)
+    }
+
+    /// Take back the last locked piece by restarting the game and replaying
+    /// every placement before it. Only modes with a fixed board and piece
+    /// order allow this, since the replay has to deal the same pieces.
+    pub fn undo(&mut self) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+
+        let mut placements = std::mem::take(&mut self.placements);
+        placements.pop();
+        let (stats, show_help) = (self.stats.clone(), self.show_help);
+
+        self.reset();
+        self.stats.elapsed = stats.elapsed;
+        self.stats.finesse_faults = stats.finesse_faults;
+        self.show_help = show_help;
+        for (piece, kick, held) in placements {
+            if held {
+                self.hold();
+            }
+            self.current_piece = piece;
+            self.last_kick = kick;
+            self.lock_piece();
+        }
+        true
+    }
+
+    /// Start a new game with the same ruleset and mode.
     pub fn reset(&mut self) {
-        *self = GameState::new();
+        let mode = std::mem::replace(&mut self.mode, Box::new(Endless));
+        *self = GameState::build(self.ruleset, mode, self.seed);
     }
 }
 
@@ -131,6 +592,8 @@ impl Default for GameState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::mode::Sprint;
+    use crate::game::timing::Timing;
 
     #[test]
     fn test_game_state_creation() {
@@ -151,10 +614,10 @@ mod tests {
     #[test]
     fn test_piece_rotation() {
         let mut state = GameState::new();
-        let initial_shape = state.current_piece.shape.clone();
+        let initial_rotation = state.current_piece.rotation;
         
         state.try_rotate();
-        assert_ne!(state.current_piece.shape, initial_shape);
+        assert_ne!(state.current_piece.rotation, initial_rotation);
     }
 
     #[test]
@@ -167,4 +630,300 @@ mod tests {
         assert!(!state.game_over);
         assert_eq!(state.scoring.score, 0);
     }
+
+    #[test]
+    fn test_reset_keeps_ruleset() {
+        let mut state = GameState::with_ruleset(Ruleset::six_wide());
+        assert_eq!(state.board.width(), 6);
+        assert!(state.board.is_valid_position(&state.current_piece));
+
+        state.reset();
+        assert_eq!(state.ruleset, Ruleset::six_wide());
+        assert_eq!(state.board.width(), 6);
+    }
+
+    #[test]
+    fn test_garbage_lifts_overlapping_piece() {
+        let mut state = GameState::new();
+        let bottom = state.board.height() - 1;
+        state.current_piece = Tetromino::new(TetrominoType::O);
+        state.current_piece.y = bottom - 1;
+        let hole = state.current_piece.x;
+
+        state.add_garbage(2, hole);
+        assert!(!state.game_over);
+        assert!(state.board.is_valid_position(&state.current_piece));
+        assert_eq!(state.current_piece.y, bottom - 3);
+    }
+
+    #[test]
+    fn test_spawn_into_stack_ends_game() {
+        let mut state = GameState::new();
+        state.board.place_piece(&state.next_piece);
+        state.current_piece.x = 0;
+        state.current_piece.y = 10;
+
+        state.hard_drop();
+        assert!(state.game_over);
+        assert_eq!(state.outcome, Some(Outcome::ToppedOut));
+    }
+
+    #[test]
+    fn test_garbage_past_buffer_ends_game() {
+        let mut state = GameState::new();
+        let total = (state.board.height() + crate::config::BUFFER_HEIGHT) as u32;
+
+        state.add_garbage(total, 0);
+        assert!(!state.game_over);
+
+        state.add_garbage(1, 0);
+        assert!(state.game_over);
+        assert_eq!(state.outcome, Some(Outcome::ToppedOut));
+    }
+
+    /// Starts with a garbage row and fails after a fixed number of pieces.
+    struct Countdown {
+        pieces: u32,
+    }
+
+    impl GameMode for Countdown {
+        fn name(&self) -> &'static str {
+            "countdown"
+        }
+
+        fn setup_board(&mut self, board: &mut Board) {
+            board.add_garbage(1, 0);
+        }
+
+        fn drop_interval(&self, _scoring: &Scoring) -> f32 {
+            0.01
+        }
+
+        fn check_end(&mut self, ctx: &ModeContext) -> Option<Outcome> {
+            (ctx.stats.pieces_placed >= self.pieces).then_some(Outcome::Failed)
+        }
+    }
+
+    #[test]
+    fn test_mode_controls_board_gravity_and_end() {
+        let mode = Countdown { pieces: 2 };
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(mode));
+        assert!(state.board.has_block(1, state.board.height() - 1));
+
+        while !state.game_over {
+            state.update(0.02);
+        }
+        assert_eq!(state.outcome, Some(Outcome::Failed));
+        assert_eq!(state.stats.pieces_placed, 2);
+
+        state.reset();
+        assert_eq!(state.mode().name(), "countdown");
+        assert!(!state.game_over);
+        assert_eq!(state.outcome, None);
+    }
+
+    /// Instant gravity with a short lock delay and entry delay.
+    struct TwentyG;
+
+    impl GameMode for TwentyG {
+        fn name(&self) -> &'static str {
+            "20g"
+        }
+
+        fn timing(&self, _scoring: &Scoring) -> Timing {
+            Timing {
+                gravity: 20.0,
+                lock_delay: 3,
+                are: 5,
+                line_clear_delay: 0,
+            }
+        }
+    }
+
+    #[test]
+    fn test_frame_gravity_lock_delay_and_are() {
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(TwentyG));
+        let frame = 1.0 / FRAMES_PER_SECOND;
+
+        state.update(frame * 1.5);
+        assert_eq!(state.get_ghost_piece(), state.current_piece);
+        assert_eq!(state.stats.pieces_placed, 0);
+
+        state.update(frame * 2.0);
+        assert_eq!(state.stats.pieces_placed, 1);
+        assert!(state.is_entry_delay());
+        let spawn_y = state.current_piece.y;
+
+        state.update(frame * 5.0);
+        assert!(!state.is_entry_delay());
+        assert_eq!(state.current_piece.y, spawn_y);
+
+        state.update(frame * 1.5);
+        assert!(state.current_piece.y > spawn_y);
+    }
+
+    #[test]
+    fn test_fading_stack_and_outline() {
+        let ruleset = Ruleset::standard().with_stack(StackVisibility::Fading(2.0), true);
+        let mut state = GameState::with_ruleset(ruleset);
+        let bottom = state.board.height() - 1;
+        for x in 1..state.board.width() {
+            state.board.set_block(x, bottom, Some(Block::Garbage));
+            state
+                .board
+                .set_block((x + 1) / 2, bottom - 1, Some(Block::Garbage));
+        }
+        state.current_piece = Tetromino::new(TetrominoType::I);
+        state.try_rotate();
+        state.current_piece.x -= state.current_piece.get_blocks()[0].0;
+
+        state.stats.elapsed = 10.0;
+        state.hard_drop();
+        assert!(state.is_outline_shown());
+        assert_eq!(
+            state.board.get_block(0, bottom),
+            Some(Block::Piece(TetrominoType::I))
+        );
+        assert!(state.is_block_visible(0, bottom));
+
+        state.stats.elapsed = 12.0;
+        assert!(!state.is_outline_shown());
+        assert!(!state.is_block_visible(0, bottom));
+        assert!(state.is_block_visible(1, bottom));
+
+        state.game_over = true;
+        assert!(state.is_block_visible(0, bottom));
+    }
+
+    #[test]
+    fn test_countdown_holds_play() {
+        let mut state = GameState::with_mode(Ruleset::standard(), Box::new(Sprint::new()));
+        let spawn_y = state.current_piece.y;
+        assert!(state.is_counting_down());
+
+        state.update(2.0);
+        state.update(2.0);
+        assert!(!state.is_counting_down());
+        assert_eq!(state.current_piece.y, spawn_y);
+        assert_eq!(state.stats.elapsed, 0.0);
+
+        state.update(0.5);
+        assert_eq!(state.stats.elapsed, 0.5);
+    }
+
+    #[test]
+    fn test_rotation_uses_wall_kicks() {
+        let mut state = GameState::new();
+        state.current_piece = Tetromino::new(TetrominoType::T);
+        state.current_piece.rotate();
+        state.current_piece.x = -1;
+
+        assert!(state.try_rotate_ccw());
+        assert_eq!(state.current_piece.rotation, 0);
+        assert_eq!(state.current_piece.x, 0);
+    }
+
+    #[test]
+    fn test_tspin_double_is_detected() {
+        let mut state = GameState::new();
+        let bottom = state.board.height() - 1;
+        for x in 0..state.board.width() {
+            if x != 4 {
+                state.board.set_block(x, bottom, Some(Block::Garbage));
+            }
+            if !(3..=5).contains(&x) {
+                state.board.set_block(x, bottom - 1, Some(Block::Garbage));
+            }
+        }
+        state.board.set_block(3, bottom - 2, Some(Block::Garbage));
+
+        state.current_piece = Tetromino::new(TetrominoType::T);
+        state.current_piece.rotation = 3;
+        state.current_piece.y = bottom - 2;
+        assert!(state.board.is_valid_position(&state.current_piece));
+
+        assert!(state.try_rotate_ccw());
+        state.hard_drop();
+
+        let clear = state.scoring.last_clear.expect("lines were cleared");
+        assert_eq!(clear.label(), "T-SPIN DOUBLE");
+        assert_eq!(state.scoring.lines_cleared, 2);
+    }
+
+    #[test]
+    fn test_hold_swaps_once_per_piece() {
+        let mut state = GameState::new();
+        let first = state.current_piece.piece_type;
+        let second = state.next_piece.piece_type;
+
+        assert!(state.hold());
+        assert_eq!(state.hold_piece, Some(first));
+        assert_eq!(state.current_piece.piece_type, second);
+        assert!(!state.hold());
+
+        state.hard_drop();
+        let third = state.current_piece.piece_type;
+        assert!(state.hold());
+        assert_eq!(state.hold_piece, Some(third));
+        assert_eq!(state.current_piece.piece_type, first);
+        assert_eq!(state.current_piece.y, 0);
+    }
+
+    #[test]
+    fn test_seeded_games_deal_the_same_pieces() {
+        let deal = |state: &mut GameState| {
+            (0..5)
+                .map(|_| {
+                    let piece = state.current_piece.piece_type;
+                    state.hard_drop();
+                    piece
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut state = GameState::with_seed(Ruleset::standard(), Box::new(Endless), 3);
+        let pieces = deal(&mut state);
+
+        state.reset();
+        assert_eq!(deal(&mut state), pieces);
+        let mut other = GameState::with_seed(Ruleset::standard(), Box::new(Endless), 3);
+        assert_eq!(deal(&mut other), pieces);
+    }
+
+    #[test]
+    fn test_finesse_faults_are_counted() {
+        let mut state = GameState::new();
+        state.press(Input::Left);
+        state.press(Input::DasLeft);
+        assert_eq!(state.piece_inputs(), [Input::DasLeft]);
+        state.hard_drop();
+        assert_eq!(state.stats.finesse_faults, 0);
+        assert!(state.piece_inputs().is_empty());
+
+        state.press(Input::Left);
+        state.press(Input::Right);
+        state.hard_drop();
+        assert_eq!(state.stats.finesse_faults, 1);
+        let fault = state.last_finesse_fault().unwrap();
+        assert_eq!(fault.used, [Input::Left, Input::Right]);
+        assert!(fault.optimal.is_empty());
+
+        // Pieces placed without key presses are not judged.
+        state.apply_input(Input::Left);
+        state.apply_input(Input::Right);
+        state.hard_drop();
+        assert_eq!(state.stats.finesse_faults, 1);
+    }
+
+    #[test]
+    fn test_das_replaces_its_tap_after_other_presses() {
+        let mut state = GameState::new();
+        state.current_piece = Tetromino::new(TetrominoType::T);
+        state.press(Input::Left);
+        state.press(Input::RotateCw);
+        state.press(Input::DasLeft);
+        assert_eq!(state.piece_inputs(), [Input::DasLeft, Input::RotateCw]);
+
+        state.hard_drop();
+        assert_eq!(state.stats.finesse_faults, 0);
+    }
 }
@@ -0,0 +1,57 @@
+/// Running statistics for a single game that are not part of the score.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameStats {
+    /// Seconds of play since the game started.
+    pub elapsed: f32,
+    pub pieces_placed: u32,
+    /// Pieces locked with more key presses than the finesse table allows.
+    pub finesse_faults: u32,
+}
+
+impl GameStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pieces placed per second of play.
+    pub fn pieces_per_second(&self) -> f32 {
+        if self.elapsed > 0.0 {
+            self.pieces_placed as f32 / self.elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Format seconds as `M:SS.mmm`.
+pub fn format_time(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u32;
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0.0), "0:00.000");
+        assert_eq!(format_time(83.4567), "1:23.457");
+    }
+
+    #[test]
+    fn test_pieces_per_second() {
+        let stats = GameStats {
+            elapsed: 4.0,
+            pieces_placed: 10,
+            ..GameStats::default()
+        };
+        assert_eq!(stats.pieces_per_second(), 2.5);
+        assert_eq!(GameStats::new().pieces_per_second(), 0.0);
+    }
+}
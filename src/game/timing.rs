@@ -0,0 +1,64 @@
+use crate::config::FRAMES_PER_SECOND;
+
+/// Gravity and phase delays for the falling piece, in 60 Hz frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timing {
+    /// Rows the piece falls per frame. One is "1G"; twenty drops it to the
+    /// floor on the frame it appears.
+    pub gravity: f32,
+    /// Frames a grounded piece waits before locking. Falling a row starts
+    /// the wait over.
+    pub lock_delay: u32,
+    /// Frames between a lock and the next piece becoming playable.
+    pub are: u32,
+    /// Extra frames added to `are` after a lock that cleared lines.
+    pub line_clear_delay: u32,
+}
+
+impl Timing {
+    /// Classic timing: fall one row every `seconds`, lock after the same
+    /// wait on the ground, and spawn the next piece straight away.
+    pub fn from_drop_interval(seconds: f32) -> Self {
+        let frames = seconds * FRAMES_PER_SECOND;
+        Timing {
+            gravity: 1.0 / frames,
+            lock_delay: (frames.round() as u32).max(1),
+            are: 0,
+            line_clear_delay: 0,
+        }
+    }
+
+    /// Frames to wait before the next piece after clearing `lines` lines.
+    pub fn entry_delay(&self, lines: u32) -> u32 {
+        if lines > 0 {
+            self.are + self.line_clear_delay
+        } else {
+            self.are
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_drop_interval() {
+        let timing = Timing::from_drop_interval(0.5);
+        assert!((timing.gravity - 1.0 / 30.0).abs() < 1e-6);
+        assert_eq!(timing.lock_delay, 30);
+        assert_eq!(timing.entry_delay(4), 0);
+    }
+
+    #[test]
+    fn test_entry_delay_adds_line_clear() {
+        let timing = Timing {
+            gravity: 20.0,
+            lock_delay: 30,
+            are: 25,
+            line_clear_delay: 40,
+        };
+        assert_eq!(timing.entry_delay(0), 25);
+        assert_eq!(timing.entry_delay(2), 65);
+    }
+}
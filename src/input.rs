@@ -1,16 +1,85 @@
+use crate::ai::{Coach, Input};
+use crate::config::DAS_SECONDS;
 use crate::game::GameState;
 use macroquad::prelude::*;
 
-pub struct InputHandler;
+/// Reads the keyboard. Left and right move one column per press, and
+/// holding one for `DAS_SECONDS` slides the piece to the wall.
+#[derive(Default)]
+pub struct InputHandler {
+    /// The direction held, -1 for left, 1 for right, 0 for neither.
+    das_direction: i32,
+    /// Seconds `das_direction` has been held.
+    das_timer: f32,
+}
 
 impl InputHandler {
-    pub fn handle_input(state: &mut GameState) {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle_input(&mut self, state: &mut GameState, dt: f32) {
         if state.game_over {
+            self.das_direction = 0;
             Self::handle_game_over_input(state);
             return;
         }
 
+        let das = self.charge_das(dt);
+        if state.is_counting_down() || state.is_entry_delay() {
+            if is_key_pressed(KeyCode::H) {
+                state.toggle_help();
+            }
+            return;
+        }
+
         Self::handle_gameplay_input(state);
+        // The press that charges DAS counts toward finesse; keeping the key
+        // held afterwards, even into the next piece, does not.
+        match das {
+            Some((input, true)) => _ = state.press(input),
+            Some((input, false)) => _ = state.apply_input(input),
+            None => {}
+        }
+    }
+
+    /// Track how long left or right has been held, returning the slide to
+    /// apply once DAS is charged and whether it just charged.
+    fn charge_das(&mut self, dt: f32) -> Option<(Input, bool)> {
+        let left = is_key_down(KeyCode::A) || is_key_down(KeyCode::Left);
+        let right = is_key_down(KeyCode::D) || is_key_down(KeyCode::Right);
+        let direction = match (left, right) {
+            (true, false) => -1,
+            (false, true) => 1,
+            _ => 0,
+        };
+        if direction != self.das_direction {
+            self.das_direction = direction;
+            self.das_timer = 0.0;
+            return None;
+        }
+        if direction == 0 {
+            return None;
+        }
+
+        let was_charged = self.das_timer >= DAS_SECONDS;
+        self.das_timer += dt;
+        if self.das_timer < DAS_SECONDS {
+            return None;
+        }
+        let input = if direction < 0 {
+            Input::DasLeft
+        } else {
+            Input::DasRight
+        };
+        Some((input, !was_charged))
+    }
+
+    /// Cycle the coaching overlay off, to the placement, to the full path.
+    pub fn handle_hint_input(coach: &mut Coach) {
+        if is_key_pressed(KeyCode::I) {
+            coach.level = coach.level.next();
+        }
     }
 
     fn handle_gameplay_input(state: &mut GameState) {
@@ -19,11 +88,11 @@ impl InputHandler {
         }
 
         if is_key_pressed(KeyCode::A) || is_key_pressed(KeyCode::Left) {
-            state.try_move(-1, 0);
+            state.press(Input::Left);
         }
 
         if is_key_pressed(KeyCode::D) || is_key_pressed(KeyCode::Right) {
-            state.try_move(1, 0);
+            state.press(Input::Right);
         }
 
         if is_key_pressed(KeyCode::S) || is_key_pressed(KeyCode::Down) {
@@ -31,22 +100,42 @@ impl InputHandler {
         }
 
         if is_key_pressed(KeyCode::W) || is_key_pressed(KeyCode::Up) {
-            state.try_rotate();
+            state.press(Input::RotateCw);
+        }
+
+        if is_key_pressed(KeyCode::Q) || is_key_pressed(KeyCode::Z) {
+            state.press(Input::RotateCcw);
         }
 
         if is_key_pressed(KeyCode::Space) {
             state.hard_drop();
         }
 
+        if is_key_pressed(KeyCode::C) || is_key_pressed(KeyCode::LeftShift) {
+            state.hold();
+        }
+
         if is_key_pressed(KeyCode::H) {
             state.toggle_help();
         }
+
+        if is_key_pressed(KeyCode::U) {
+            state.undo();
+        }
+
+        if is_key_pressed(KeyCode::R) {
+            state.reset();
+        }
     }
 
     fn handle_game_over_input(state: &mut GameState) {
         if is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Enter) {
             state.reset();
         }
+
+        if is_key_pressed(KeyCode::U) {
+            state.undo();
+        }
     }
 
     fn any_key_pressed() -> bool {
@@ -58,6 +147,10 @@ impl InputHandler {
             || is_key_pressed(KeyCode::Down)
             || is_key_pressed(KeyCode::W)
             || is_key_pressed(KeyCode::Up)
+            || is_key_pressed(KeyCode::Q)
+            || is_key_pressed(KeyCode::Z)
             || is_key_pressed(KeyCode::Space)
+            || is_key_pressed(KeyCode::C)
+            || is_key_pressed(KeyCode::LeftShift)
     }
 }
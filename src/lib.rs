@@ -1,10 +1,14 @@
+pub mod ai;
 pub mod config;
+pub mod env;
 pub mod game;
 pub mod input;
 pub mod renderer;
+pub mod tbp;
 pub mod tetromino;
 
-pub use game::board::Board;
+pub use game::board::{Block, Board};
+pub use game::ruleset::Ruleset;
 pub use game::scoring::Scoring;
 pub use game::state::GameState;
 pub use tetromino::bag::PieceBag;
@@ -1,16 +1,16 @@
 use macroquad::prelude::*;
 use macroquad::rand::srand;
 
-mod config;
-mod game;
-mod input;
-mod renderer;
-mod tetromino;
-
-use config::*;
-use game::GameState;
-use input::InputHandler;
-use renderer::Renderer;
+use rust_tetris::ai::{AutoPlayer, Bot, Coach, HintLevel, Weights};
+use rust_tetris::config::*;
+use rust_tetris::game::mode::pc::parse_openers;
+use rust_tetris::game::mode::puzzle::parse_puzzles;
+use rust_tetris::game::mode::{self, GameMode, ModeOptions, Opener, Puzzle, MODE_NAMES};
+use rust_tetris::game::StackVisibility;
+use rust_tetris::input::InputHandler;
+use rust_tetris::renderer::Renderer;
+use rust_tetris::tbp::{TbpBot, TbpPlayer};
+use rust_tetris::{GameState, Ruleset};
 
 fn window_conf() -> Conf {
     Conf {
@@ -22,17 +22,205 @@ fn window_conf() -> Conf {
     }
 }
 
+/// Value following `flag` on the command line, if any.
+fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag);
+    args.next();
+    args.next()
+}
+
+/// Board from `--board <preset|WIDTHxHEIGHT>`, falling back to the standard
+/// 10x20 field, with the stack shown as `--stack` and `--outline` ask.
+fn ruleset_from_args() -> Ruleset {
+    let ruleset = arg_value("--board").map_or_else(Ruleset::default, |value| {
+        value.parse().unwrap_or_else(|err| {
+            eprintln!("{err}; using the standard board");
+            Ruleset::default()
+        })
+    });
+
+    let stack = arg_value("--stack").map_or(StackVisibility::Visible, |value| {
+        value.parse().unwrap_or_else(|err| {
+            eprintln!("{err}; showing the whole stack");
+            StackVisibility::Visible
+        })
+    });
+    ruleset.with_stack(stack, has_flag("--outline"))
+}
+
+/// Numeric value following `flag`, ignoring anything that does not parse.
+fn arg_number(flag: &str) -> Option<u32> {
+    arg_value(flag).and_then(|value| value.parse().ok())
+}
+
+fn has_flag(flag: &str) -> bool {
+    std::env::args().any(|arg| arg == flag)
+}
+
+/// Puzzles from the file named by `--puzzle-file`, if one was given and it
+/// holds any.
+fn puzzles_from_args() -> Option<Vec<Puzzle>> {
+    let path = arg_value("--puzzle-file")?;
+    let puzzles = std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| parse_puzzles(&text));
+
+    match puzzles {
+        Ok(puzzles) if !puzzles.is_empty() => Some(puzzles),
+        Ok(_) => {
+            eprintln!("{path} has no puzzles; using the bundled ones");
+            None
+        }
+        Err(err) => {
+            eprintln!("{path}: {err}; using the bundled puzzles");
+            None
+        }
+    }
+}
+
+/// Openers from the file named by `--opener-file`, if one was given and it
+/// holds any.
+fn openers_from_args() -> Option<Vec<Opener>> {
+    let path = arg_value("--opener-file")?;
+    let openers = std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| parse_openers(&text));
+
+    match openers {
+        Ok(openers) if !openers.is_empty() => Some(openers),
+        Ok(_) => {
+            eprintln!("{path} has no openers; using the bundled ones");
+            None
+        }
+        Err(err) => {
+            eprintln!("{path}: {err}; using the bundled openers");
+            None
+        }
+    }
+}
+
+/// Game mode from `--mode <name>` plus its `--level`, `--high`, `--rows`,
+/// `--lines`, `--variable-goal`, `--puzzle`, `--puzzle-file`, `--opener`
+/// and `--opener-file` settings, falling back to endless play.
+fn mode_from_args() -> Box<dyn GameMode> {
+    let name = arg_value("--mode").unwrap_or_else(|| MODE_NAMES[0].to_owned());
+    let options = ModeOptions {
+        level: arg_number("--level"),
+        high: arg_number("--high"),
+        rows: arg_number("--rows"),
+        lines: arg_number("--lines"),
+        variable_goal: has_flag("--variable-goal"),
+        puzzle: arg_number("--puzzle"),
+        puzzles: puzzles_from_args(),
+        opener: arg_number("--opener"),
+        openers: openers_from_args(),
+    };
+
+    mode::from_name(&name, &options).unwrap_or_else(|| {
+        eprintln!(
+            "unknown mode '{name}', expected one of: {}",
+            MODE_NAMES.join(", ")
+        );
+        mode::from_name(MODE_NAMES[0], &options).expect("default mode exists")
+    })
+}
+
+/// Who is playing: the keyboard, the built-in bot or an external TBP bot.
+enum Player {
+    Keyboard(InputHandler),
+    Bot(AutoPlayer),
+    Tbp(TbpPlayer),
+}
+
+impl Player {
+    fn update(&mut self, state: &mut GameState, dt: f32) {
+        match self {
+            Player::Keyboard(input) => input.handle_input(state, dt),
+            Player::Bot(player) => player.update(state, dt),
+            Player::Tbp(player) => {
+                if let Err(err) = player.update(state, dt) {
+                    eprintln!(
+                        "TBP bot '{}' failed: {err}; handing back to the keyboard",
+                        player.name
+                    );
+                    *self = Player::Keyboard(InputHandler::new());
+                }
+            }
+        }
+    }
+}
+
+/// Pieces a second from `--pps`, 0 for no limit.
+fn pps_from_args() -> f32 {
+    arg_value("--pps").map_or(AUTOPLAY_PPS, |value| {
+        value.parse().unwrap_or_else(|_| {
+            eprintln!("invalid --pps '{value}'; using {AUTOPLAY_PPS}");
+            AUTOPLAY_PPS
+        })
+    })
+}
+
+/// Weights from the file named by `--weights`, falling back to the
+/// built-in ones.
+fn weights_from_args() -> Weights {
+    let Some(path) = arg_value("--weights") else {
+        return Weights::default();
+    };
+    Weights::load(&path).unwrap_or_else(|err| {
+        eprintln!("{path}: {err}; using the built-in weights");
+        Weights::default()
+    })
+}
+
+/// The built-in bot with `--autoplay`, or the bot launched by
+/// `--tbp "<command> [args]"`, falling back to the keyboard.
+fn player_from_args() -> Player {
+    if let Some(command) = arg_value("--tbp") {
+        let mut words = command.split_whitespace();
+        let program = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        match TbpBot::spawn(program, &args) {
+            Ok(bot) => return Player::Tbp(TbpPlayer::new(bot, Some(pps_from_args()))),
+            Err(err) => eprintln!("could not start TBP bot '{command}': {err}"),
+        }
+    }
+
+    if has_flag("--autoplay") {
+        return Player::Bot(AutoPlayer::new(
+            Bot::new(weights_from_args()),
+            Some(pps_from_args()),
+        ));
+    }
+    Player::Keyboard(InputHandler::new())
+}
+
+/// Placement hints from the start with `--hint`, numbered key presses too
+/// with `--hint-path`. Either way they can be cycled in game.
+fn hint_level_from_args() -> HintLevel {
+    if has_flag("--hint-path") {
+        HintLevel::Path
+    } else if has_flag("--hint") {
+        HintLevel::Placement
+    } else {
+        HintLevel::Off
+    }
+}
+
 #[macroquad::main(window_conf)]
 async fn main() {
     srand(macroquad::miniquad::date::now() as u64);
 
-    let mut game_state = GameState::new();
+    let mut game_state = GameState::with_mode(ruleset_from_args(), mode_from_args());
     let mut renderer = Renderer::new();
+    renderer.coach = Coach::new(Bot::new(weights_from_args()), hint_level_from_args());
+    renderer.show_finesse = has_flag("--finesse") || game_state.mode().shows_finesse();
+    let mut player = player_from_args();
 
     loop {
         let dt = get_frame_time();
 
-        InputHandler::handle_input(&mut game_state);
+        player.update(&mut game_state, dt);
+        InputHandler::handle_hint_input(&mut renderer.coach);
 
         game_state.update(dt);
 
@@ -1,42 +1,118 @@
+use crate::ai::{Coach, Hint, HintLevel};
 use crate::config::*;
-use crate::game::GameState;
+use crate::game::board::{Block, Board};
+use crate::game::finesse::input_label;
+use crate::game::stats::format_time;
+use crate::game::{GameState, Goal, Outcome};
+use crate::tetromino::{Tetromino, TetrominoType};
 use macroquad::prelude::*;
 
+/// Screen geometry of the playfield, computed from the board being drawn so
+/// narrow, tall and oversized boards all fit the window.
+#[derive(Clone, Copy)]
+struct BoardLayout {
+    cols: i32,
+    rows: i32,
+    block_size: f32,
+}
+
+impl BoardLayout {
+    fn new(board: &Board) -> Self {
+        let fit_width =
+            (WINDOW_WIDTH as f32 - BOARD_OFFSET_X - UI_PANEL_WIDTH) / board.width() as f32;
+        let fit_height = (WINDOW_HEIGHT as f32 - BOARD_OFFSET_Y * 2.0) / board.height() as f32;
+
+        BoardLayout {
+            cols: board.width(),
+            rows: board.height(),
+            block_size: BLOCK_SIZE.min(fit_width).min(fit_height).floor(),
+        }
+    }
+
+    fn width(&self) -> f32 {
+        self.cols as f32 * self.block_size
+    }
+
+    fn height(&self) -> f32 {
+        self.rows as f32 * self.block_size
+    }
+
+    /// Top-left screen position of a board cell.
+    fn cell_origin(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            BOARD_OFFSET_X + x * self.block_size,
+            BOARD_OFFSET_Y + y * self.block_size,
+        )
+    }
+}
+
 pub struct Renderer {
     blink_timer: f32,
+    /// Works out the placement hint when one is shown.
+    pub coach: Coach,
+    /// Show the finesse fault count and flash the right inputs after each
+    /// fault.
+    pub show_finesse: bool,
 }
 
 impl Renderer {
     pub fn new() -> Self {
-        Renderer { blink_timer: 0.0 }
+        Renderer {
+            blink_timer: 0.0,
+            coach: Coach::default(),
+            show_finesse: false,
+        }
     }
 
     pub fn update(&mut self, dt: f32) {
         self.blink_timer += dt;
     }
 
-    pub fn draw(&self, state: &GameState) {
+    pub fn draw(&mut self, state: &GameState) {
         clear_background(GB_LIGHT);
 
-        self.draw_gb_border();
-        self.draw_board(state);
-        self.draw_ghost_piece(state);
-        self.draw_current_piece(state);
-        self.draw_ui(state);
+        let layout = BoardLayout::new(&state.board);
+
+        self.draw_gb_border(&layout);
+        self.draw_board(state, &layout);
+        if let Some(guide) = state.mode().guide() {
+            Self::draw_guide(guide, &state.board, &layout);
+        }
+        if !state.is_entry_delay() {
+            if let Some(target) = state.mode().target() {
+                Self::draw_piece_outline(&target, &layout);
+            }
+            if !state.game_over {
+                self.coach.update(state);
+                if let Some(hint) = self.coach.hint() {
+                    self.draw_hint(hint, self.coach.level, &layout);
+                }
+            }
+            self.draw_ghost_piece(state, &layout);
+            self.draw_current_piece(state, &layout);
+        }
+        self.draw_ui(state, &layout);
+        if self.show_finesse {
+            self.draw_finesse_fault(state);
+        }
+
+        if state.is_counting_down() {
+            self.draw_countdown(state, &layout);
+        }
 
         if state.show_help {
-            self.draw_help();
+            self.draw_help(&layout);
         }
 
         if state.game_over {
-            self.draw_game_over();
+            self.draw_game_over(state);
         }
     }
 
-    fn draw_gb_border(&self) {
+    fn draw_gb_border(&self, layout: &BoardLayout) {
         let border_thickness = 8.0;
-        let board_width = BOARD_WIDTH as f32 * BLOCK_SIZE;
-        let board_height = BOARD_HEIGHT as f32 * BLOCK_SIZE;
+        let board_width = layout.width();
+        let board_height = layout.height();
 
         draw_rectangle(
             BOARD_OFFSET_X - border_thickness,
@@ -71,84 +147,226 @@ impl Renderer {
         );
     }
 
-    fn draw_board(&self, state: &GameState) {
-        for x in 0..=BOARD_WIDTH {
-            let px = BOARD_OFFSET_X + x as f32 * BLOCK_SIZE;
+    fn draw_board(&self, state: &GameState, layout: &BoardLayout) {
+        for x in 0..=layout.cols {
+            let px = BOARD_OFFSET_X + x as f32 * layout.block_size;
             draw_line(
                 px,
                 BOARD_OFFSET_Y,
                 px,
-                BOARD_OFFSET_Y + BOARD_HEIGHT as f32 * BLOCK_SIZE,
+                BOARD_OFFSET_Y + layout.height(),
                 1.0,
                 GB_MED_LIGHT,
             );
         }
 
-        for y in 0..=BOARD_HEIGHT {
-            let py = BOARD_OFFSET_Y + y as f32 * BLOCK_SIZE;
+        for y in 0..=layout.rows {
+            let py = BOARD_OFFSET_Y + y as f32 * layout.block_size;
             draw_line(
                 BOARD_OFFSET_X,
                 py,
-                BOARD_OFFSET_X + BOARD_WIDTH as f32 * BLOCK_SIZE,
+                BOARD_OFFSET_X + layout.width(),
                 py,
                 1.0,
                 GB_MED_LIGHT,
             );
         }
 
-        for y in 0..BOARD_HEIGHT {
-            for x in 0..BOARD_WIDTH {
-                if state.board.has_block(x, y) {
-                    self.draw_gb_block(x as f32, y as f32);
+        let outline = state.is_outline_shown();
+        for y in 0..layout.rows {
+            for x in 0..layout.cols {
+                let Some(block) = state.board.get_block(x, y) else {
+                    continue;
+                };
+                if state.is_block_visible(x, y) {
+                    self.draw_gb_block(layout, x as f32, y as f32, block);
+                } else if outline {
+                    self.draw_stack_outline(state, layout, x, y);
                 }
             }
         }
     }
 
-    fn draw_ghost_piece(&self, state: &GameState) {
+    /// Trace the edges of a hidden cell that face empty space, so a hidden
+    /// stack shows its shape without its contents.
+    fn draw_stack_outline(&self, state: &GameState, layout: &BoardLayout, x: i32, y: i32) {
+        let (px, py) = layout.cell_origin(x as f32, y as f32);
+        let size = layout.block_size;
+        let edges = [
+            ((0, -1), (px, py, px + size, py)),
+            ((1, 0), (px + size, py, px + size, py + size)),
+            ((0, 1), (px, py + size, px + size, py + size)),
+            ((-1, 0), (px, py, px, py + size)),
+        ];
+
+        for ((dx, dy), (x1, y1, x2, y2)) in edges {
+            let (nx, ny) = (x + dx, y + dy);
+            let open = (0..layout.cols).contains(&nx)
+                && ny < layout.rows
+                && !state.board.has_block(nx, ny);
+            if open {
+                draw_line(x1, y1, x2, y2, 2.0, GB_DARK);
+            }
+        }
+    }
+
+    fn draw_ghost_piece(&self, state: &GameState, layout: &BoardLayout) {
         let ghost = state.get_ghost_piece();
         for (x, y) in ghost.get_blocks() {
             if y >= 0 {
-                self.draw_ghost_block(x as f32, y as f32);
+                self.draw_ghost_block(layout, x as f32, y as f32);
             }
         }
     }
 
-    fn draw_current_piece(&self, state: &GameState) {
+    /// Outline the bot's recommended placement and, at `HintLevel::Path`,
+    /// number where each key press leaves the piece.
+    fn draw_hint(&self, hint: &Hint, level: HintLevel, layout: &BoardLayout) {
+        Self::draw_piece_outline(&hint.placement.piece, layout);
+
+        if level != HintLevel::Path {
+            return;
+        }
+        let font_size = (layout.block_size * 0.8).max(10.0);
+        for (step, piece) in hint.path.iter().enumerate() {
+            let blocks = piece.get_blocks();
+            let x = blocks.iter().map(|&(x, _)| x as f32).sum::<f32>() / 4.0;
+            let y = blocks.iter().map(|&(_, y)| y as f32).sum::<f32>() / 4.0;
+            if y < 0.0 {
+                continue;
+            }
+            let (px, py) = layout.cell_origin(x + 0.25, y + 0.85);
+            draw_text(&(step + 1).to_string(), px, py, font_size, GB_DARK);
+        }
+    }
+
+    /// Shade the cells of a shape still to be built, faintly enough to
+    /// read as empty.
+    fn draw_guide(guide: &Board, board: &Board, layout: &BoardLayout) {
+        let inset = (layout.block_size * 0.2).floor();
+        let size = layout.block_size - inset * 2.0;
+        for y in 0..layout.rows {
+            for x in (0..layout.cols).filter(|&x| guide.has_block(x, y) && !board.has_block(x, y)) {
+                let (px, py) = layout.cell_origin(x as f32, y as f32);
+                draw_rectangle(px + inset, py + inset, size, size, GB_MED_LIGHT);
+            }
+        }
+    }
+
+    /// Trace the outer edges of a piece with a heavy line, so it stands
+    /// apart from the ghost piece's double squares.
+    fn draw_piece_outline(piece: &Tetromino, layout: &BoardLayout) {
+        let blocks = piece.get_blocks();
+        let size = layout.block_size;
+
+        for &(x, y) in blocks.iter().filter(|&&(_, y)| y >= 0) {
+            let (px, py) = layout.cell_origin(x as f32, y as f32);
+            let edges = [
+                ((0, -1), (px, py, px + size, py)),
+                ((1, 0), (px + size, py, px + size, py + size)),
+                ((0, 1), (px, py + size, px + size, py + size)),
+                ((-1, 0), (px, py, px, py + size)),
+            ];
+            for ((dx, dy), (x1, y1, x2, y2)) in edges {
+                if !blocks.contains(&(x + dx, y + dy)) {
+                    draw_line(x1, y1, x2, y2, 3.0, GB_DARK);
+                }
+            }
+        }
+    }
+
+    fn draw_current_piece(&self, state: &GameState, layout: &BoardLayout) {
+        let block = Block::Piece(state.current_piece.piece_type);
         for (x, y) in state.current_piece.get_blocks() {
             if y >= 0 {
-                self.draw_gb_block(x as f32, y as f32);
+                self.draw_gb_block(layout, x as f32, y as f32, block);
             }
         }
     }
 
-    fn draw_gb_block(&self, x: f32, y: f32) {
-        let px = BOARD_OFFSET_X + x * BLOCK_SIZE + 1.0;
-        let py = BOARD_OFFSET_Y + y * BLOCK_SIZE + 1.0;
-        let size = BLOCK_SIZE - 2.0;
+    fn draw_gb_block(&self, layout: &BoardLayout, x: f32, y: f32, block: Block) {
+        let (px, py) = layout.cell_origin(x, y);
+
+        Self::draw_block_tile(px + 1.0, py + 1.0, layout.block_size - 2.0, block);
+    }
+
+    /// Draw a single tile using the shade and pattern of its piece type,
+    /// so locked cells stay recognisable with only four Game Boy colors.
+    fn draw_block_tile(px: f32, py: f32, size: f32, block: Block) {
+        let (fill, accent) = Self::block_shades(block);
+        let inset = (size * 0.25).floor();
+        let inner = size - inset * 2.0;
 
         draw_rectangle(px, py, size, size, GB_DARK);
-        draw_rectangle(px + 1.0, py + 1.0, size - 6.0, size - 6.0, GB_MED_DARK);
-        draw_rectangle(px + 3.0, py + 3.0, size - 10.0, size - 10.0, GB_MED_LIGHT);
+        draw_rectangle(px + 1.0, py + 1.0, size - 2.0, size - 2.0, fill);
+
+        match block {
+            Block::Piece(TetrominoType::I) => {
+                draw_rectangle(px + 1.0, py + inset, size - 2.0, inner, accent);
+            }
+            Block::Piece(TetrominoType::O) | Block::Piece(TetrominoType::S) => {
+                draw_rectangle(px + inset, py + inset, inner, inner, accent);
+            }
+            Block::Piece(TetrominoType::T) | Block::Piece(TetrominoType::J) => {
+                draw_rectangle(px + 1.0, py + 1.0, size - 6.0, size - 6.0, accent);
+                draw_rectangle(px + 3.0, py + 3.0, size - 10.0, size - 10.0, fill);
+            }
+            Block::Piece(TetrominoType::Z) => {
+                draw_rectangle_lines(px + inset, py + inset, inner, inner, 2.0, accent);
+            }
+            Block::Piece(TetrominoType::L) => {
+                draw_rectangle(px + inset, py + 1.0, inner, size - 2.0, accent);
+            }
+            Block::Garbage => {
+                let (left, top) = (px + 1.0, py + 1.0);
+                let (right, bottom) = (px + size - 1.0, py + size - 1.0);
+                draw_line(left, top, right, bottom, 2.0, accent);
+                draw_line(right, top, left, bottom, 2.0, accent);
+            }
+        }
+    }
+
+    fn block_shades(block: Block) -> (Color, Color) {
+        match block {
+            Block::Piece(TetrominoType::I) => (GB_MED_LIGHT, GB_MED_DARK),
+            Block::Piece(TetrominoType::O) => (GB_LIGHT, GB_DARK),
+            Block::Piece(TetrominoType::T) => (GB_MED_LIGHT, GB_MED_DARK),
+            Block::Piece(TetrominoType::S) => (GB_MED_DARK, GB_LIGHT),
+            Block::Piece(TetrominoType::Z) => (GB_MED_LIGHT, GB_DARK),
+            Block::Piece(TetrominoType::J) => (GB_LIGHT, GB_MED_DARK),
+            Block::Piece(TetrominoType::L) => (GB_MED_DARK, GB_MED_LIGHT),
+            Block::Garbage => (GB_MED_DARK, GB_DARK),
+        }
     }
 
-    fn draw_ghost_block(&self, x: f32, y: f32) {
-        let px = BOARD_OFFSET_X + x * BLOCK_SIZE + 1.0;
-        let py = BOARD_OFFSET_Y + y * BLOCK_SIZE + 1.0;
-        let size = BLOCK_SIZE - 2.0;
+    fn draw_ghost_block(&self, layout: &BoardLayout, x: f32, y: f32) {
+        let (px, py) = layout.cell_origin(x, y);
+        let (px, py) = (px + 1.0, py + 1.0);
+        let size = layout.block_size - 2.0;
 
         draw_rectangle_lines(px, py, size, size, 1.0, GB_MED_DARK);
         draw_rectangle_lines(px + 2.0, py + 2.0, size - 4.0, size - 4.0, 1.0, GB_MED_DARK);
     }
 
-    fn draw_ui(&self, state: &GameState) {
-        let ui_x = BOARD_OFFSET_X + BOARD_WIDTH as f32 * BLOCK_SIZE + 30.0;
+    fn draw_ui(&self, state: &GameState, layout: &BoardLayout) {
+        let ui_x = BOARD_OFFSET_X + layout.width() + 30.0;
         let mut ui_y = BOARD_OFFSET_Y;
 
         draw_text("TETRIS", ui_x, ui_y, 20.0, GB_DARK);
-        ui_y += 35.0;
+        ui_y += 14.0;
+        draw_text(
+            &state.mode().name().to_uppercase(),
+            ui_x,
+            ui_y,
+            10.0,
+            GB_MED_DARK,
+        );
+        ui_y += 21.0;
+
+        self.draw_piece_box("NEXT", Some(state.next_piece.piece_type), ui_x, ui_y);
+        ui_y += 100.0;
 
-        self.draw_next_piece_box(state, ui_x, ui_y);
+        self.draw_piece_box("HOLD", state.hold_piece, ui_x, ui_y);
         ui_y += 100.0;
 
         draw_text("SCORE", ui_x, ui_y, 12.0, GB_DARK);
@@ -170,7 +388,36 @@ impl Renderer {
         draw_text("LEVEL", ui_x, ui_y, 12.0, GB_DARK);
         ui_y += 15.0;
         draw_text(&format!("{:02}", state.scoring.level), ui_x, ui_y, 14.0, GB_DARK);
-        ui_y += 35.0;
+        ui_y += 25.0;
+
+        draw_text("TIME", ui_x, ui_y, 12.0, GB_DARK);
+        ui_y += 15.0;
+        draw_text(&format_time(state.stats.elapsed), ui_x, ui_y, 14.0, GB_DARK);
+        ui_y += 25.0;
+
+        if self.show_finesse {
+            draw_text("FAULTS", ui_x, ui_y, 12.0, GB_DARK);
+            ui_y += 15.0;
+            let faults = format!("{:03}", state.stats.finesse_faults);
+            draw_text(&faults, ui_x, ui_y, 14.0, GB_DARK);
+            ui_y += 25.0;
+        }
+
+        let goal = Self::goal_progress(state);
+        for (label, value) in goal.into_iter().chain(state.hud()) {
+            draw_text(label, ui_x, ui_y, 12.0, GB_DARK);
+            ui_y += 15.0;
+            draw_text(&value, ui_x, ui_y, 14.0, GB_DARK);
+            ui_y += 25.0;
+        }
+        if let Some(clear) = state.scoring.last_clear {
+            draw_text(&clear.label(), ui_x, ui_y, 10.0, GB_DARK);
+            ui_y += 12.0;
+            if clear.combo > 0 {
+                draw_text(&format!("COMBO {}", clear.combo), ui_x, ui_y, 10.0, GB_DARK);
+            }
+        }
+        ui_y += 22.0;
 
         if (self.blink_timer * 2.0) as i32 % 2 == 0 {
             draw_text("PRESS H", ui_x, ui_y, 10.0, GB_MED_DARK);
@@ -179,36 +426,96 @@ impl Renderer {
         }
     }
 
-    fn draw_next_piece_box(&self, state: &GameState, x: f32, y: f32) {
+    /// Blink the inputs the last faulted piece needed above the board for a
+    /// moment after it locks.
+    fn draw_finesse_fault(&self, state: &GameState) {
+        let Some(fault) = state.last_finesse_fault() else {
+            return;
+        };
+        let recent = state.stats.elapsed - fault.time < FINESSE_FLASH_SECONDS;
+        if !recent || (self.blink_timer * 4.0) as i32 % 2 != 0 {
+            return;
+        }
+
+        let inputs: Vec<_> = fault
+            .optimal
+            .iter()
+            .map(|&input| input_label(input))
+            .collect();
+        let sequence = if inputs.is_empty() {
+            "DROP".to_owned()
+        } else {
+            inputs.join(" ")
+        };
+        draw_text(
+            &format!("FINESSE: {sequence}"),
+            BOARD_OFFSET_X,
+            BOARD_OFFSET_Y - 14.0,
+            12.0,
+            GB_DARK,
+        );
+    }
+
+    /// What is left before the mode's goal is reached, if it has one.
+    fn goal_progress(state: &GameState) -> Option<(&'static str, String)> {
+        match state.mode().goal() {
+            Goal::None => None,
+            Goal::Lines(lines) => Some((
+                "LINES LEFT",
+                format!("{:03}", lines.saturating_sub(state.scoring.lines_cleared)),
+            )),
+            Goal::Time(seconds) => Some(("TIME LEFT", format_time(seconds - state.stats.elapsed))),
+            Goal::Score(score) => Some(("TARGET", format!("{:06}", score))),
+        }
+    }
+
+    fn draw_piece_box(&self, label: &str, piece: Option<TetrominoType>, x: f32, y: f32) {
         let box_size = 80.0;
 
         draw_rectangle(x - 4.0, y - 4.0, box_size + 8.0, box_size + 8.0, GB_DARK);
         draw_rectangle(x - 2.0, y - 2.0, box_size + 4.0, box_size + 4.0, GB_MED_DARK);
         draw_rectangle(x, y, box_size, box_size, GB_LIGHT);
 
-        draw_text("NEXT", x + 5.0, y - 8.0, 12.0, GB_DARK);
+        draw_text(label, x + 5.0, y - 8.0, 12.0, GB_DARK);
 
+        let Some(piece_type) = piece else {
+            return;
+        };
         let offset_x = x + 20.0;
         let offset_y = y + 20.0;
 
-        for (row, line) in state.next_piece.shape.iter().enumerate() {
-            for (col, &filled) in line.iter().enumerate() {
-                if filled {
-                    let px = offset_x + col as f32 * 16.0;
-                    let py = offset_y + row as f32 * 16.0;
-                    draw_rectangle(px, py, 14.0, 14.0, GB_DARK);
-                    draw_rectangle(px + 1.0, py + 1.0, 8.0, 8.0, GB_MED_DARK);
-                    draw_rectangle(px + 2.0, py + 2.0, 6.0, 6.0, GB_MED_LIGHT);
-                }
-            }
+        let block = Block::Piece(piece_type);
+        for &(col, row) in piece_type.cells(0) {
+            let px = offset_x + col as f32 * 16.0;
+            let py = offset_y + row as f32 * 16.0;
+            Self::draw_block_tile(px, py, 14.0, block);
         }
     }
 
-    fn draw_help(&self) {
+    fn draw_countdown(&self, state: &GameState, layout: &BoardLayout) {
+        let label = (state.countdown().ceil() as u32).to_string();
+        let box_width = 80.0;
+        let box_height = 50.0;
+        let box_x = BOARD_OFFSET_X + (layout.width() - box_width) / 2.0;
+        let box_y = BOARD_OFFSET_Y + (layout.height() - box_height) / 2.0;
+
+        draw_rectangle(
+            box_x - 2.0,
+            box_y - 2.0,
+            box_width + 4.0,
+            box_height + 4.0,
+            GB_DARK,
+        );
+        draw_rectangle(box_x, box_y, box_width, box_height, GB_LIGHT);
+        draw_text("READY", box_x + 18.0, box_y + 18.0, 12.0, GB_MED_DARK);
+        draw_text(&label, box_x + 32.0, box_y + 40.0, 20.0, GB_DARK);
+    }
+
+    fn draw_help(&self, layout: &BoardLayout) {
         let help_x = BOARD_OFFSET_X - 20.0;
-        let help_y = BOARD_OFFSET_Y + BOARD_HEIGHT as f32 * BLOCK_SIZE + 20.0;
-        let help_width = BOARD_WIDTH as f32 * BLOCK_SIZE + 40.0;
-        let help_height = 140.0;
+        let help_y = BOARD_OFFSET_Y + layout.height() + 20.0;
+        let help_width = (layout.width() + 40.0).max(280.0);
+        let help_height = 215.0;
 
         draw_rectangle(
             help_x - 4.0,
@@ -232,12 +539,46 @@ impl Renderer {
 
         draw_text("A/← D/→  Move left/right", help_x + 10.0, text_y, 10.0, GB_DARK);
         text_y += 15.0;
-        draw_text("W/↑      Rotate piece", help_x + 10.0, text_y, 10.0, GB_DARK);
+        draw_text(
+            "W/↑      Rotate clockwise",
+            help_x + 10.0,
+            text_y,
+            10.0,
+            GB_DARK,
+        );
+        text_y += 15.0;
+        draw_text(
+            "Q/Z      Rotate counter-clockwise",
+            help_x + 10.0,
+            text_y,
+            10.0,
+            GB_DARK,
+        );
         text_y += 15.0;
         draw_text("S/↓      Soft drop", help_x + 10.0, text_y, 10.0, GB_DARK);
         text_y += 15.0;
         draw_text("SPACE    Hard drop", help_x + 10.0, text_y, 10.0, GB_DARK);
         text_y += 15.0;
+        draw_text("C/SHIFT  Hold", help_x + 10.0, text_y, 10.0, GB_DARK);
+        text_y += 15.0;
+        draw_text(
+            "U        Undo (puzzles)",
+            help_x + 10.0,
+            text_y,
+            10.0,
+            GB_DARK,
+        );
+        text_y += 15.0;
+        draw_text("R        Retry", help_x + 10.0, text_y, 10.0, GB_DARK);
+        text_y += 15.0;
+        draw_text(
+            "I        Cycle AI hints",
+            help_x + 10.0,
+            text_y,
+            10.0,
+            GB_DARK,
+        );
+        text_y += 15.0;
         draw_text("H        Toggle this help", help_x + 10.0, text_y, 10.0, GB_DARK);
 
         if (self.blink_timer * 3.0) as i32 % 2 == 0 {
@@ -251,15 +592,16 @@ impl Renderer {
         }
     }
 
-    fn draw_game_over(&self) {
+    fn draw_game_over(&self, state: &GameState) {
         let overlay_color = Color::new(GB_DARK.r, GB_DARK.g, GB_DARK.b, 0.8);
         draw_rectangle(0.0, 0.0, screen_width(), screen_height(), overlay_color);
 
         let center_x = screen_width() / 2.0;
         let center_y = screen_height() / 2.0;
 
-        let box_width = 200.0;
-        let box_height = 100.0;
+        let results = state.results();
+        let box_width = 280.0;
+        let box_height = 114.0 + results.len() as f32 * 16.0;
         let box_x = center_x - box_width / 2.0;
         let box_y = center_y - box_height / 2.0;
 
@@ -279,16 +621,31 @@ impl Renderer {
         );
         draw_rectangle(box_x, box_y, box_width, box_height, GB_LIGHT);
 
-        draw_text("GAME OVER", box_x + 20.0, box_y + 30.0, 16.0, GB_DARK);
+        let title = match state.outcome {
+            Some(Outcome::Completed) => state.mode().completion_title(),
+            Some(Outcome::Failed) => "FAILED",
+            Some(Outcome::ToppedOut) | None => "GAME OVER",
+        };
+        draw_text(title, box_x + 20.0, box_y + 30.0, 16.0, GB_DARK);
+
+        let mut text_y = box_y + 55.0;
+        for (label, value) in &results {
+            draw_text(label, box_x + 20.0, text_y, 12.0, GB_DARK);
+            draw_text(value, box_x + 100.0, text_y, 12.0, GB_DARK);
+            text_y += 16.0;
+        }
 
         if (self.blink_timer * 2.0) as i32 % 2 == 0 {
             draw_text(
                 "SPACE TO RESTART",
                 box_x + 10.0,
-                box_y + 55.0,
+                text_y + 14.0,
                 12.0,
                 GB_MED_DARK,
             );
+            if state.can_undo() {
+                draw_text("U TO UNDO", box_x + 10.0, text_y + 28.0, 12.0, GB_MED_DARK);
+            }
         }
     }
 }
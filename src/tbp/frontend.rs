@@ -0,0 +1,508 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use super::{piece_at, start_from, BotMessage, FrontendMessage, Move};
+use crate::ai::{find_placements, Placement};
+use crate::config::{AUTOPLAY_RESTART_SECONDS, TBP_TIMEOUT_SECONDS};
+use crate::game::scoring::Spin;
+use crate::game::GameState;
+use crate::tetromino::Tetromino;
+
+/// A connection to a TBP bot, already past the `info`/`rules`/`ready`
+/// handshake.
+pub struct TbpBot<R, W> {
+    reader: R,
+    writer: W,
+    pub name: String,
+    pub author: String,
+    child: Option<Child>,
+}
+
+impl TbpBot<BufReader<ChildStdout>, ChildStdin> {
+    /// Launch `program` with `args` and talk TBP over its stdin and stdout.
+    /// A bot that does not finish the handshake within
+    /// `TBP_TIMEOUT_SECONDS` is killed.
+    pub fn spawn(program: &str, args: &[&str]) -> io::Result<Self> {
+        Self::spawn_within(program, args, Duration::from_secs_f32(TBP_TIMEOUT_SECONDS))
+    }
+
+    fn spawn_within(program: &str, args: &[&str], timeout: Duration) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let reader = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let writer = child.stdin.take().expect("stdin is piped");
+
+        match Self::connect(reader, writer, timeout) {
+            Ok(mut bot) => {
+                bot.child = Some(child);
+                Ok(bot)
+            }
+            Err(err) => {
+                // Killing the bot also ends a handshake still waiting on it.
+                let _ = child.kill();
+                let _ = child.wait();
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<R, W> TbpBot<R, W>
+where
+    R: BufRead + Send + 'static,
+    W: Write + Send + 'static,
+{
+    /// Like `new`, but with the handshake on a thread of its own, giving up
+    /// on a bot that has not finished it after `timeout`.
+    pub fn connect(reader: R, writer: W, timeout: Duration) -> io::Result<Self> {
+        let (done, handshake) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = done.send(Self::new(reader, writer));
+        });
+        handshake.recv_timeout(timeout).map_err(|err| match err {
+            RecvTimeoutError::Timeout => {
+                io::Error::new(io::ErrorKind::TimedOut, "bot took too long to start")
+            }
+            RecvTimeoutError::Disconnected => hung_up(),
+        })?
+    }
+}
+
+impl<R: BufRead, W: Write> TbpBot<R, W> {
+    pub fn new(reader: R, writer: W) -> io::Result<Self> {
+        let mut bot = TbpBot {
+            reader,
+            writer,
+            name: String::new(),
+            author: String::new(),
+            child: None,
+        };
+
+        let BotMessage::Info { name, author, .. } = bot.receive()? else {
+            return Err(invalid_data("bot did not start with info"));
+        };
+        bot.name = name;
+        bot.author = author;
+
+        bot.send(&FrontendMessage::Rules {})?;
+        match bot.receive()? {
+            BotMessage::Ready => Ok(bot),
+            _ => Err(invalid_data("bot did not accept the rules")),
+        }
+    }
+
+    pub fn send(&mut self, message: &FrontendMessage) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, message)?;
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+
+    /// The next message from the bot, turning its `error` into an `Err`.
+    pub fn receive(&mut self) -> io::Result<BotMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        match serde_json::from_str(&line)? {
+            BotMessage::Error { reason } => Err(invalid_data(&reason)),
+            message => Ok(message),
+        }
+    }
+
+    /// Ask for moves in the current position, best first.
+    pub fn suggest(&mut self) -> io::Result<Vec<Move>> {
+        self.send(&FrontendMessage::Suggest)?;
+        match self.receive()? {
+            BotMessage::Suggestion { moves } => Ok(moves),
+            _ => Err(invalid_data("expected a suggestion")),
+        }
+    }
+}
+
+impl<R, W> Drop for TbpBot<R, W> {
+    fn drop(&mut self) {
+        // Make sure a launched bot does not outlive the game.
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+fn invalid_data(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_owned())
+}
+
+/// Work for the thread that talks to the bot.
+enum Request {
+    Send(FrontendMessage),
+    /// Ask for moves, numbered so a late answer can be told apart.
+    Suggest(u64),
+}
+
+/// A numbered suggestion, or the error that stopped the bot thread.
+type Reply = io::Result<(u64, Vec<Move>)>;
+
+/// A suggestion asked for and not answered yet.
+#[derive(Clone, Copy)]
+struct Pending {
+    id: u64,
+    /// Pieces placed when it was asked for. If gravity locks the piece
+    /// first, the answer is for a piece that is gone.
+    pieces: u32,
+    /// Seconds waited so far.
+    waited: f32,
+}
+
+/// Plays a `GameState` with an external TBP bot: sends it the position,
+/// carries out the first suggestion the game allows and keeps it informed of
+/// new pieces, pacing itself to `pps` pieces a second like `AutoPlayer`.
+///
+/// The bot is talked to on a thread of its own, so a slow bot never holds
+/// up a frame, and one that takes longer than `TBP_TIMEOUT_SECONDS` to
+/// suggest a move is given up on with an error.
+pub struct TbpPlayer {
+    pub name: String,
+    pub pps: Option<f32>,
+    wait: f32,
+    started: bool,
+    requests: Sender<Request>,
+    replies: Receiver<Reply>,
+    pending: Option<Pending>,
+    asked: u64,
+    /// A launched bot, killed along with the player so a hung one does not
+    /// keep its thread blocked.
+    child: Option<Child>,
+}
+
+impl TbpPlayer {
+    pub fn new<R, W>(mut bot: TbpBot<R, W>, pps: Option<f32>) -> Self
+    where
+        R: BufRead + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let (requests, received) = mpsc::channel();
+        let (replied, replies) = mpsc::channel();
+        let name = bot.name.clone();
+        let child = bot.child.take();
+
+        thread::spawn(move || {
+            for request in received {
+                let reply = match request {
+                    Request::Send(message) => match bot.send(&message) {
+                        Ok(()) => continue,
+                        Err(err) => Err(err),
+                    },
+                    Request::Suggest(id) => bot.suggest().map(|moves| (id, moves)),
+                };
+                let failed = reply.is_err();
+                if replied.send(reply).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        TbpPlayer {
+            name,
+            pps: pps.filter(|&pps| pps > 0.0),
+            wait: 0.0,
+            started: false,
+            requests,
+            replies,
+            pending: None,
+            asked: 0,
+            child,
+        }
+    }
+
+    /// Run one frame: ask for a move when the next piece is due, and carry
+    /// it out once the bot has answered.
+    pub fn update(&mut self, state: &mut GameState, dt: f32) -> io::Result<()> {
+        self.wait -= dt;
+
+        if state.game_over {
+            self.pending = None;
+            if self.started {
+                self.send(FrontendMessage::Stop)?;
+                self.started = false;
+            }
+            if self.wait <= -AUTOPLAY_RESTART_SECONDS {
+                state.reset();
+                self.wait = 0.0;
+            }
+            return Ok(());
+        }
+
+        if let Some(pending) = &mut self.pending {
+            pending.waited += dt;
+            let late = pending.waited >= TBP_TIMEOUT_SECONDS;
+            return match self.replies.try_recv() {
+                Ok(reply) => self.answer(state, reply),
+                Err(TryRecvError::Empty) if !late => Ok(()),
+                Err(TryRecvError::Empty) => Err(timed_out()),
+                Err(TryRecvError::Disconnected) => Err(hung_up()),
+            };
+        }
+
+        if state.is_counting_down() || state.is_entry_delay() || self.wait > 0.0 {
+            return Ok(());
+        }
+
+        self.wait = match self.pps {
+            Some(pps) => self.wait.max(0.0) + 1.0 / pps,
+            None => 0.0,
+        };
+        self.ask(state)
+    }
+
+    /// Ask the bot for a move and carry it out, waiting for it for up to
+    /// `TBP_TIMEOUT_SECONDS`.
+    pub fn play_piece(&mut self, state: &mut GameState) -> io::Result<()> {
+        self.ask(state)?;
+        let timeout = Duration::from_secs_f32(TBP_TIMEOUT_SECONDS);
+        while self.pending.is_some() {
+            let reply = self
+                .replies
+                .recv_timeout(timeout)
+                .map_err(|err| match err {
+                    RecvTimeoutError::Timeout => timed_out(),
+                    RecvTimeoutError::Disconnected => hung_up(),
+                })?;
+            self.answer(state, reply)?;
+        }
+        Ok(())
+    }
+
+    fn ask(&mut self, state: &GameState) -> io::Result<()> {
+        if !self.started {
+            self.send(FrontendMessage::Start(start_from(state)))?;
+            self.started = true;
+        }
+        self.asked += 1;
+        self.pending = Some(Pending {
+            id: self.asked,
+            pieces: state.stats.pieces_placed,
+            waited: 0.0,
+        });
+        self.request(Request::Suggest(self.asked))
+    }
+
+    /// Carry out the suggestion `reply` holds, unless it answers an older
+    /// question or the piece it was for has locked since.
+    fn answer(&mut self, state: &mut GameState, reply: Reply) -> io::Result<()> {
+        let (id, moves) = reply?;
+        let Some(pending) = self.pending.filter(|pending| pending.id == id) else {
+            return Ok(());
+        };
+        self.pending = None;
+
+        if state.stats.pieces_placed != pending.pieces {
+            self.send(FrontendMessage::Stop)?;
+            self.started = false;
+            return Ok(());
+        }
+        self.play(state, moves)
+    }
+
+    /// Play the first of `moves` the game allows. If none can be played,
+    /// the piece is hard dropped where it is and the bot is restarted from
+    /// the new position.
+    fn play(&mut self, state: &mut GameState, moves: Vec<Move>) -> io::Result<()> {
+        let Some((mv, hold, placement)) = moves
+            .into_iter()
+            .find_map(|mv| plan(state, &mv).map(|(hold, placement)| (mv, hold, placement)))
+        else {
+            state.hard_drop();
+            self.send(FrontendMessage::Stop)?;
+            self.started = false;
+            return Ok(());
+        };
+
+        // Holding into an empty slot deals an extra piece.
+        let dealt_two = hold && state.hold_piece.is_none();
+        if hold {
+            state.hold();
+        }
+        for input in placement.inputs {
+            state.apply_input(input);
+        }
+
+        self.send(FrontendMessage::Play { mv })?;
+        if dealt_two {
+            self.send_new_piece(state.current_piece.piece_type.letter())?;
+        }
+        self.send_new_piece(state.next_piece.piece_type.letter())
+    }
+
+    fn send_new_piece(&mut self, piece: char) -> io::Result<()> {
+        self.send(FrontendMessage::NewPiece { piece })
+    }
+
+    fn send(&mut self, message: FrontendMessage) -> io::Result<()> {
+        self.request(Request::Send(message))
+    }
+
+    fn request(&self, request: Request) -> io::Result<()> {
+        self.requests.send(request).map_err(|_| hung_up())
+    }
+}
+
+impl Drop for TbpPlayer {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        "bot took too long to suggest a move",
+    )
+}
+
+/// The bot thread has stopped, after reporting why.
+fn hung_up() -> io::Error {
+    io::Error::from(io::ErrorKind::BrokenPipe)
+}
+
+/// Whether `mv` needs a hold first and the inputs that reach it, if the
+/// game can play it at all.
+fn plan(state: &GameState, mv: &Move) -> Option<(bool, Placement)> {
+    let target = piece_at(&mv.location, &state.board)?;
+    let hold = target.piece_type != state.current_piece.piece_type;
+    let start = if hold {
+        let held = state.hold_piece.unwrap_or(state.next_piece.piece_type);
+        if !state.can_hold() || held != target.piece_type {
+            return None;
+        }
+        Tetromino::spawn(held, state.board.width())
+    } else {
+        state.current_piece
+    };
+
+    let mut cells = target.get_blocks();
+    cells.sort_unstable();
+    let spin = Spin::from(mv.spin);
+    let placements = find_placements(&state.board, &start);
+    let matching = |placement: &&Placement| placement.cells() == cells;
+    let placement = placements
+        .iter()
+        .filter(matching)
+        .find(|placement| placement.spin == spin)
+        .or_else(|| placements.iter().find(matching))?;
+    Some((hold, placement.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tbp::server::pipe::connect;
+    use crate::tbp::server::{Position, TbpStrategy};
+    use crate::tbp::StubBot;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_handshake_reads_bot_info() {
+        let bot = connect(StubBot);
+        assert_eq!(bot.name, "rust-tetris stub");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_silent_bot_is_killed_at_handshake() {
+        let started = std::time::Instant::now();
+        let result = TbpBot::spawn_within("sleep", &["60"], Duration::from_millis(100));
+        assert_eq!(
+            result.err().map(|err| err.kind()),
+            Some(io::ErrorKind::TimedOut)
+        );
+        // Waiting on the bot returned at once, so it was killed.
+        assert!(started.elapsed() < Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_stub_bot_plays_pieces() {
+        let mut state = GameState::new();
+        let mut player = TbpPlayer::new(connect(StubBot), None);
+
+        for _ in 0..5 {
+            player.play_piece(&mut state).unwrap();
+        }
+        assert_eq!(state.stats.pieces_placed, 5);
+        assert!(!state.board.is_empty());
+    }
+
+    /// Answers nothing until the test holding the other end is done.
+    struct Stalled(Mutex<Receiver<()>>);
+
+    impl TbpStrategy for Stalled {
+        fn name(&self) -> &str {
+            "stalled"
+        }
+
+        fn suggest(&self, _position: &Position) -> Vec<Move> {
+            let _ = self.0.lock().unwrap().recv();
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_frames_go_on_while_the_bot_thinks() {
+        let mut state = GameState::new();
+        let mut player = TbpPlayer::new(connect(StubBot), None);
+
+        for _ in 0..1000 {
+            player.update(&mut state, 1.0 / 60.0).unwrap();
+            state.update(1.0 / 60.0);
+            if state.stats.pieces_placed == 3 {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("the bot never played");
+    }
+
+    #[test]
+    fn test_slow_bot_times_out() {
+        let (hang_up, stalled) = mpsc::channel();
+        let mut state = GameState::new();
+        let mut player = TbpPlayer::new(connect(Stalled(Mutex::new(stalled))), None);
+
+        player.update(&mut state, 0.0).unwrap();
+        player
+            .update(&mut state, TBP_TIMEOUT_SECONDS / 2.0)
+            .unwrap();
+        let err = player.update(&mut state, TBP_TIMEOUT_SECONDS).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(state.stats.pieces_placed, 0);
+        drop(hang_up);
+    }
+
+    #[test]
+    fn test_plan_uses_hold() {
+        let state = GameState::new();
+        let next = state.next_piece.piece_type;
+        let mut piece = Tetromino::spawn(next, state.board.width());
+        while state.board.is_valid_position(&piece) {
+            piece.y += 1;
+        }
+        piece.y -= 1;
+        let mv = Move {
+            location: crate::tbp::location_of(&piece, &state.board),
+            spin: crate::tbp::TbpSpin::None,
+        };
+
+        let (hold, placement) = plan(&state, &mv).unwrap();
+        assert_eq!(hold, next != state.current_piece.piece_type);
+        assert_eq!(placement.piece.get_blocks(), piece.get_blocks());
+    }
+}
@@ -0,0 +1,85 @@
+use super::server::{Position, TbpStrategy};
+use super::{location_of, Move};
+use crate::ai::Bot;
+use crate::tetromino::Tetromino;
+
+/// Moves offered per suggestion, in case the frontend cannot play the best.
+const SUGGESTIONS: usize = 5;
+
+/// The built-in evaluator as a TBP bot. It weighs every placement of the
+/// current piece against every placement of the piece it could hold.
+impl TbpStrategy for Bot {
+    fn name(&self) -> &str {
+        "rust-tetris"
+    }
+
+    fn suggest(&self, position: &Position) -> Vec<Move> {
+        let board = &position.board;
+        let candidates = position
+            .queue
+            .front()
+            .copied()
+            .into_iter()
+            .chain(position.hold_alternative());
+
+        let mut ranked: Vec<_> = candidates
+            .flat_map(|piece_type| {
+                self.ranked_placements(board, &Tetromino::spawn(piece_type, board.width()))
+            })
+            .collect();
+        // Stable, so the current piece wins ties against the held one.
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranked
+            .into_iter()
+            .take(SUGGESTIONS)
+            .map(|(placement, _)| Move {
+                location: location_of(&placement.piece, board),
+                spin: placement.spin.into(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::{Block, Board};
+    use crate::game::GameState;
+    use crate::tbp::server::pipe::connect;
+    use crate::tbp::TbpPlayer;
+    use crate::tetromino::TetrominoType;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_suggests_holding_for_the_better_piece() {
+        let mut board = Board::new();
+        for y in board.height() - 4..board.height() {
+            for x in 0..board.width() - 1 {
+                board.set_block(x, y, Some(Block::Garbage));
+            }
+        }
+        let position = Position {
+            board,
+            queue: VecDeque::from([TetrominoType::S, TetrominoType::I]),
+            hold: None,
+        };
+
+        let moves = Bot::default().suggest(&position);
+        assert!(moves.len() <= SUGGESTIONS);
+        assert_eq!(moves[0].location.piece, 'I');
+        assert_eq!(moves[0].location.x, 9);
+    }
+
+    #[test]
+    fn test_heuristic_bot_over_tbp() {
+        let mut state = GameState::new();
+        let mut player = TbpPlayer::new(connect(Bot::default()), None);
+
+        for _ in 0..100 {
+            player.play_piece(&mut state).unwrap();
+        }
+        assert!(!state.game_over);
+        assert!(state.scoring.lines_cleared >= 30);
+    }
+}
@@ -0,0 +1,333 @@
+//! The Tetris Bot Protocol: JSON messages, one per line, between a frontend
+//! that runs the game and a bot that suggests moves. See
+//! <https://github.com/tetris-bot-protocol/tbp-spec>.
+
+pub mod frontend;
+pub mod heuristic;
+pub mod server;
+pub mod stub;
+
+pub use frontend::{TbpBot, TbpPlayer};
+pub use server::{Position, TbpServer, TbpStrategy};
+pub use stub::StubBot;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::BUFFER_HEIGHT;
+use crate::game::board::{Block, Board};
+use crate::game::ruleset::MAX_BOARD_WIDTH;
+use crate::game::scoring::Spin;
+use crate::game::{GameState, Ruleset};
+use crate::tetromino::{Tetromino, TetrominoType};
+
+/// Messages a frontend sends to a bot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrontendMessage {
+    Rules {},
+    Start(Start),
+    Suggest,
+    Play {
+        #[serde(rename = "move")]
+        mv: Move,
+    },
+    NewPiece {
+        piece: char,
+    },
+    Stop,
+    Quit,
+}
+
+/// Messages a bot sends to a frontend.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    Info {
+        name: String,
+        version: String,
+        author: String,
+        features: Vec<String>,
+    },
+    Ready,
+    Suggestion {
+        moves: Vec<Move>,
+    },
+    Error {
+        reason: String,
+    },
+}
+
+/// The position a bot should start thinking from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Start {
+    pub hold: Option<char>,
+    /// Known pieces, the one in play first.
+    pub queue: Vec<char>,
+    pub combo: u32,
+    pub back_to_back: bool,
+    /// Rows from the bottom up, each cell a piece letter, `G` for garbage
+    /// or empty.
+    pub board: Vec<Vec<Option<char>>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    pub location: PieceLocation,
+    pub spin: TbpSpin,
+}
+
+/// A piece by its SRS rotation centre, with `y` counted up from the bottom
+/// row.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PieceLocation {
+    #[serde(rename = "type")]
+    pub piece: char,
+    pub orientation: Orientation,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Orientation {
+    const ALL: [Orientation; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    fn from_rotation(rotation: u8) -> Self {
+        Self::ALL[rotation as usize % 4]
+    }
+
+    fn rotation(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TbpSpin {
+    None,
+    Mini,
+    Full,
+}
+
+impl From<Spin> for TbpSpin {
+    fn from(spin: Spin) -> Self {
+        match spin {
+            Spin::None => TbpSpin::None,
+            Spin::Mini => TbpSpin::Mini,
+            Spin::Full => TbpSpin::Full,
+        }
+    }
+}
+
+impl From<TbpSpin> for Spin {
+    fn from(spin: TbpSpin) -> Self {
+        match spin {
+            TbpSpin::None => Spin::None,
+            TbpSpin::Mini => Spin::Mini,
+            TbpSpin::Full => Spin::Full,
+        }
+    }
+}
+
+/// Cells of each piece pointing north, relative to its rotation centre
+/// with `y` up, as TBP defines them.
+fn north_cells(piece_type: TetrominoType) -> [(i32, i32); 4] {
+    match piece_type {
+        TetrominoType::I => [(-1, 0), (0, 0), (1, 0), (2, 0)],
+        TetrominoType::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        TetrominoType::T => [(-1, 0), (0, 0), (1, 0), (0, 1)],
+        TetrominoType::S => [(-1, 0), (0, 0), (0, 1), (1, 1)],
+        TetrominoType::Z => [(-1, 1), (0, 1), (0, 0), (1, 0)],
+        TetrominoType::J => [(-1, 0), (0, 0), (1, 0), (-1, 1)],
+        TetrominoType::L => [(-1, 0), (0, 0), (1, 0), (1, 1)],
+    }
+}
+
+/// TBP cells of a piece, in board coordinates with `y` down.
+fn location_cells(
+    piece_type: TetrominoType,
+    location: &PieceLocation,
+    board: &Board,
+) -> [(i32, i32); 4] {
+    north_cells(piece_type).map(|(mut dx, mut dy)| {
+        for _ in 0..location.orientation.rotation() {
+            (dx, dy) = (dy, -dx);
+        }
+        (location.x + dx, board.height() - 1 - (location.y + dy))
+    })
+}
+
+fn sorted(mut cells: [(i32, i32); 4]) -> [(i32, i32); 4] {
+    cells.sort_unstable();
+    cells
+}
+
+/// Where `piece` is in TBP terms.
+pub fn location_of(piece: &Tetromino, board: &Board) -> PieceLocation {
+    let mut location = PieceLocation {
+        piece: piece.piece_type.letter(),
+        orientation: Orientation::from_rotation(piece.rotation),
+        x: 0,
+        y: 0,
+    };
+    let origin = sorted(location_cells(piece.piece_type, &location, board))[0];
+    let target = sorted(piece.get_blocks())[0];
+    location.x = target.0 - origin.0;
+    location.y = origin.1 - target.1;
+    location
+}
+
+/// The piece a TBP location describes, if it names one.
+pub fn piece_at(location: &PieceLocation, board: &Board) -> Option<Tetromino> {
+    let piece_type = TetrominoType::from_letter(location.piece)?;
+    let target = sorted(location_cells(piece_type, location, board));
+    let mut piece = Tetromino {
+        piece_type,
+        rotation: location.orientation.rotation(),
+        x: 0,
+        y: 0,
+    };
+    let origin = sorted(piece.get_blocks())[0];
+    piece.move_by(target[0].0 - origin.0, target[0].1 - origin.1);
+    Some(piece)
+}
+
+/// The board as TBP rows, bottom first, hidden buffer rows included.
+pub fn board_rows(board: &Board) -> Vec<Vec<Option<char>>> {
+    (-BUFFER_HEIGHT..board.height())
+        .rev()
+        .map(|y| {
+            (0..board.width())
+                .map(|x| match board.get_block(x, y) {
+                    Some(Block::Piece(piece_type)) => Some(piece_type.letter()),
+                    Some(Block::Garbage) => Some('G'),
+                    None => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A board rebuilt from TBP rows, treating all but the top `BUFFER_HEIGHT`
+/// as visible. Fails if the rows are uneven or make a board this game
+/// cannot play on.
+pub fn board_from_rows(rows: &[Vec<Option<char>>]) -> Result<Board, String> {
+    let width = rows.first().map_or(0, |row| row.len()) as i32;
+    let height = rows.len() as i32 - BUFFER_HEIGHT;
+    if rows.iter().any(|row| row.len() != width as usize) {
+        return Err("board rows differ in width".to_owned());
+    }
+    if !(4..=MAX_BOARD_WIDTH).contains(&width) || height < 4 {
+        return Err(format!(
+            "a board {width} wide with {} rows is out of range",
+            rows.len()
+        ));
+    }
+
+    let mut board = Board::with_ruleset(&Ruleset::new(width, height));
+    for (row, y) in rows.iter().zip((-BUFFER_HEIGHT..height).rev()) {
+        for (x, cell) in row.iter().enumerate() {
+            let block = cell.map(|letter| match TetrominoType::from_letter(letter) {
+                Some(piece_type) => Block::Piece(piece_type),
+                None => Block::Garbage,
+            });
+            board.set_block(x as i32, y, block);
+        }
+    }
+    Ok(board)
+}
+
+/// The `start` message for a game in progress.
+pub fn start_from(state: &GameState) -> Start {
+    Start {
+        hold: state.hold_piece.map(|piece_type| piece_type.letter()),
+        queue: vec![
+            state.current_piece.piece_type.letter(),
+            state.next_piece.piece_type.letter(),
+        ],
+        combo: state.scoring.combo_count(),
+        back_to_back: state.scoring.is_back_to_back(),
+        board: board_rows(&state.board),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_round_trip() {
+        let board = Board::new();
+        for piece_type in TetrominoType::all() {
+            for rotation in 0..4 {
+                let mut piece = Tetromino::new(piece_type);
+                piece.rotation = rotation;
+                piece.y = 10;
+                let location = location_of(&piece, &board);
+                assert_eq!(piece_at(&location, &board), Some(piece));
+            }
+        }
+    }
+
+    #[test]
+    fn test_location_uses_rotation_centre() {
+        let board = Board::new();
+        let mut piece = Tetromino::new(TetrominoType::T);
+        piece.y = board.height() - 2;
+        // T flat on the floor, pointing up, centred on column 4.
+        let location = location_of(&piece, &board);
+        assert_eq!((location.x, location.y), (4, 0));
+        assert_eq!(location.orientation, Orientation::North);
+    }
+
+    #[test]
+    fn test_message_json() {
+        let message: FrontendMessage = serde_json::from_str(
+            r#"{"type":"play","move":{"location":{"type":"T","orientation":"east","x":4,"y":1},"spin":"full"}}"#,
+        )
+        .unwrap();
+        let FrontendMessage::Play { mv } = message else {
+            panic!("expected a play message");
+        };
+        assert_eq!(mv.location.orientation, Orientation::East);
+        assert_eq!(mv.spin, TbpSpin::Full);
+
+        let json = serde_json::to_string(&FrontendMessage::Suggest).unwrap();
+        assert_eq!(json, r#"{"type":"suggest"}"#);
+    }
+
+    #[test]
+    fn test_board_rows_round_trip() {
+        let mut board = Board::new();
+        board.set_block(0, board.height() - 1, Some(Block::Garbage));
+        board.set_block(3, 2, Some(Block::Piece(TetrominoType::S)));
+
+        let rows = board_rows(&board);
+        assert_eq!(rows.len(), 40);
+        assert_eq!(rows[0][0], Some('G'));
+        assert_eq!(rows[17][3], Some('S'));
+
+        let rebuilt = board_from_rows(&rows).unwrap();
+        assert_eq!(rebuilt.rows(), board.rows());
+        assert_eq!(rebuilt.get_block(3, 2), board.get_block(3, 2));
+    }
+
+    #[test]
+    fn test_board_from_bad_rows() {
+        assert!(board_from_rows(&[]).is_err());
+        assert!(board_from_rows(&vec![vec![None; 3]; 40]).is_err());
+        assert!(board_from_rows(&vec![vec![None; 33]; 40]).is_err());
+        assert!(board_from_rows(&vec![vec![None; 10]; 23]).is_err());
+
+        let mut rows = vec![vec![None; 10]; 40];
+        rows[5].push(None);
+        assert!(board_from_rows(&rows).is_err());
+    }
+}
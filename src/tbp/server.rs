@@ -0,0 +1,307 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+use super::{board_from_rows, piece_at, BotMessage, FrontendMessage, Move, Start};
+use crate::game::board::Board;
+use crate::tetromino::TetrominoType;
+
+/// What a bot knows about the game from the frontend's messages.
+pub struct Position {
+    pub board: Board,
+    /// Known pieces, the one in play first.
+    pub queue: VecDeque<TetrominoType>,
+    pub hold: Option<TetrominoType>,
+}
+
+impl Position {
+    /// The position a `start` message describes, or why it cannot be
+    /// played.
+    pub fn from_start(start: &Start) -> Result<Self, String> {
+        Ok(Position {
+            board: board_from_rows(&start.board)?,
+            queue: start
+                .queue
+                .iter()
+                .filter_map(|&letter| TetrominoType::from_letter(letter))
+                .collect(),
+            hold: start.hold.and_then(TetrominoType::from_letter),
+        })
+    }
+
+    /// The piece that would come out of hold: the held one, or the next in
+    /// the queue if nothing is held.
+    pub fn hold_alternative(&self) -> Option<TetrominoType> {
+        self.hold.or_else(|| self.queue.get(1).copied())
+    }
+
+    /// Lock a move, taking the piece from the front of the queue or, if it
+    /// names another piece, from hold.
+    pub fn play(&mut self, mv: &Move) {
+        let Some(piece) = piece_at(&mv.location, &self.board) else {
+            return;
+        };
+
+        let current = self.queue.pop_front();
+        if current != Some(piece.piece_type) {
+            if self.hold.is_none() {
+                self.queue.pop_front();
+            }
+            self.hold = current;
+        }
+        self.board.place_piece(&piece);
+        self.board.clear_lines();
+    }
+}
+
+/// How a bot picks its moves.
+pub trait TbpStrategy {
+    fn name(&self) -> &str;
+
+    /// Moves for the current position, best first. Empty if the bot has
+    /// nothing to offer.
+    fn suggest(&self, position: &Position) -> Vec<Move>;
+}
+
+/// The bot side of TBP: tracks the position through the frontend's
+/// messages and answers `suggest` with the strategy's moves.
+pub struct TbpServer<S> {
+    pub strategy: S,
+    position: Option<Position>,
+}
+
+impl<S: TbpStrategy> TbpServer<S> {
+    pub fn new(strategy: S) -> Self {
+        TbpServer {
+            strategy,
+            position: None,
+        }
+    }
+
+    pub fn position(&self) -> Option<&Position> {
+        self.position.as_ref()
+    }
+
+    pub fn info(&self) -> BotMessage {
+        BotMessage::Info {
+            name: self.strategy.name().to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            author: "rust-tetris".to_owned(),
+            features: Vec::new(),
+        }
+    }
+
+    /// Handle one message, returning the reply if it needs one.
+    pub fn respond(&mut self, message: FrontendMessage) -> Option<BotMessage> {
+        match message {
+            FrontendMessage::Rules {} => return Some(BotMessage::Ready),
+            FrontendMessage::Start(start) => match Position::from_start(&start) {
+                Ok(position) => self.position = Some(position),
+                Err(reason) => {
+                    self.position = None;
+                    return Some(BotMessage::Error { reason });
+                }
+            },
+            FrontendMessage::Suggest => {
+                let moves = self
+                    .position
+                    .as_ref()
+                    .map_or_else(Vec::new, |position| self.strategy.suggest(position));
+                return Some(BotMessage::Suggestion { moves });
+            }
+            FrontendMessage::Play { mv } => {
+                if let Some(position) = self.position.as_mut() {
+                    position.play(&mv);
+                }
+            }
+            FrontendMessage::NewPiece { piece } => {
+                if let Some(position) = self.position.as_mut() {
+                    position.queue.extend(TetrominoType::from_letter(piece));
+                }
+            }
+            FrontendMessage::Stop | FrontendMessage::Quit => self.position = None,
+        }
+        None
+    }
+
+    /// Serve TBP on `reader` and `writer` until told to quit or the input
+    /// ends. Lines that are not valid messages are skipped, and a `start`
+    /// with a board the bot cannot play is answered with an error.
+    pub fn run(mut self, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+        write_message(&mut writer, &self.info())?;
+        for line in reader.lines() {
+            let Ok(message) = serde_json::from_str::<FrontendMessage>(&line?) else {
+                continue;
+            };
+            if message == FrontendMessage::Quit {
+                break;
+            }
+            if let Some(reply) = self.respond(message) {
+                write_message(&mut writer, &reply)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_message(writer: &mut impl Write, message: &BotMessage) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+/// In-process pipes for connecting a frontend to a bot on another thread in
+/// tests.
+#[cfg(test)]
+pub(crate) mod pipe {
+    use std::io::{self, BufReader, Read, Write};
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::thread;
+
+    use super::{TbpServer, TbpStrategy};
+    use crate::tbp::TbpBot;
+
+    pub(crate) struct PipeWriter(Sender<Vec<u8>>);
+
+    impl Write for PipeWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .send(buf.to_vec())
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub(crate) struct PipeReader {
+        receiver: Receiver<Vec<u8>>,
+        pending: Vec<u8>,
+    }
+
+    impl Read for PipeReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pending.is_empty() {
+                match self.receiver.recv() {
+                    Ok(bytes) => self.pending = bytes,
+                    Err(_) => return Ok(0),
+                }
+            }
+            let len = buf.len().min(self.pending.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            Ok(len)
+        }
+    }
+
+    fn pipe() -> (PipeWriter, BufReader<PipeReader>) {
+        let (sender, receiver) = channel();
+        let reader = PipeReader {
+            receiver,
+            pending: Vec::new(),
+        };
+        (PipeWriter(sender), BufReader::new(reader))
+    }
+
+    /// A frontend connection to `strategy` served on its own thread.
+    pub(crate) fn connect(
+        strategy: impl TbpStrategy + Send + 'static,
+    ) -> TbpBot<BufReader<PipeReader>, PipeWriter> {
+        let (to_bot, bot_input) = pipe();
+        let (bot_output, from_bot) = pipe();
+        thread::spawn(move || TbpServer::new(strategy).run(bot_input, bot_output));
+        TbpBot::new(from_bot, to_bot).expect("bot handshake")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tbp::{board_rows, location_of, StubBot, TbpSpin};
+    use crate::tetromino::Tetromino;
+
+    fn start(board: &Board, queue: &[char], hold: Option<char>) -> FrontendMessage {
+        FrontendMessage::Start(Start {
+            hold,
+            queue: queue.to_vec(),
+            combo: 0,
+            back_to_back: false,
+            board: board_rows(board),
+        })
+    }
+
+    #[test]
+    fn test_handshake_and_suggestion() {
+        let mut server = TbpServer::new(StubBot);
+        assert_eq!(
+            server.respond(FrontendMessage::Rules {}),
+            Some(BotMessage::Ready)
+        );
+        assert_eq!(
+            server.respond(start(&Board::new(), &['T', 'I'], None)),
+            None
+        );
+
+        let Some(BotMessage::Suggestion { moves }) = server.respond(FrontendMessage::Suggest)
+        else {
+            panic!("expected a suggestion");
+        };
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].location.piece, 'T');
+        assert_eq!((moves[0].location.x, moves[0].location.y), (4, 0));
+        assert_eq!(moves[0].spin, TbpSpin::None);
+    }
+
+    #[test]
+    fn test_play_tracks_queue_and_hold() {
+        let mut server = TbpServer::new(StubBot);
+        server.respond(start(&Board::new(), &['T', 'I', 'O'], None));
+
+        // Playing the I holds the T.
+        let board = Board::new();
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.y = board.height() - 1;
+        let mv = Move {
+            location: location_of(&piece, &board),
+            spin: TbpSpin::None,
+        };
+        server.respond(FrontendMessage::Play { mv });
+
+        let position = server.position().unwrap();
+        assert_eq!(position.hold, Some(TetrominoType::T));
+        assert_eq!(position.queue, [TetrominoType::O]);
+        assert!(position.board.has_block(3, board.height() - 1));
+    }
+
+    #[test]
+    fn test_malformed_start_is_an_error() {
+        let mut server = TbpServer::new(StubBot);
+        let message: FrontendMessage = serde_json::from_str(
+            r#"{"type":"start","hold":null,"queue":["T"],"combo":0,"back_to_back":false,"board":[[null,null,null]]}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            server.respond(message),
+            Some(BotMessage::Error { .. })
+        ));
+        assert!(server.position().is_none());
+        assert_eq!(
+            server.respond(FrontendMessage::Suggest),
+            Some(BotMessage::Suggestion { moves: Vec::new() })
+        );
+    }
+
+    #[test]
+    fn test_stop_forgets_the_position() {
+        let mut server = TbpServer::new(StubBot);
+        server.respond(start(&Board::new(), &['T'], None));
+        server.respond(FrontendMessage::Stop);
+
+        assert!(server.position().is_none());
+        assert_eq!(
+            server.respond(FrontendMessage::Suggest),
+            Some(BotMessage::Suggestion { moves: Vec::new() })
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use super::server::{Position, TbpStrategy};
+use super::{location_of, Move};
+use crate::ai::find_placements;
+use crate::tetromino::Tetromino;
+
+/// The simplest strategy that plays TBP correctly: always drop the current
+/// piece straight down. Useful for testing frontends.
+pub struct StubBot;
+
+impl TbpStrategy for StubBot {
+    fn name(&self) -> &str {
+        "rust-tetris stub"
+    }
+
+    fn suggest(&self, position: &Position) -> Vec<Move> {
+        let Some(&piece_type) = position.queue.front() else {
+            return Vec::new();
+        };
+        let board = &position.board;
+        let piece = Tetromino::spawn(piece_type, board.width());
+        find_placements(board, &piece)
+            .into_iter()
+            .take(1)
+            .map(|placement| Move {
+                location: location_of(&placement.piece, board),
+                spin: placement.spin.into(),
+            })
+            .collect()
+    }
+}
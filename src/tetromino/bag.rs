@@ -1,16 +1,33 @@
+use super::randomizer::Randomizer;
 use super::types::TetrominoType;
-use macroquad::rand::gen_range;
+use macroquad::rand::{gen_range, RandGenerator};
 
 pub struct PieceBag {
     pieces: Vec<TetrominoType>,
     index: usize,
+    /// Private generator for a reproducible sequence; the global one is
+    /// used otherwise.
+    rng: Option<RandGenerator>,
 }
 
 impl PieceBag {
     pub fn new() -> Self {
+        Self::with_rng(None)
+    }
+
+    /// A bag whose sequence depends only on `seed`, unaffected by anything
+    /// else drawing random numbers.
+    pub fn seeded(seed: u64) -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+        Self::with_rng(Some(rng))
+    }
+
+    fn with_rng(rng: Option<RandGenerator>) -> Self {
         let mut bag = PieceBag {
             pieces: TetrominoType::all().to_vec(),
             index: 0,
+            rng,
         };
         bag.shuffle();
         bag
@@ -18,7 +35,10 @@ impl PieceBag {
 
     fn shuffle(&mut self) {
         for i in (1..self.pieces.len()).rev() {
-            let j = gen_range(0, i + 1);
+            let j = match &self.rng {
+                Some(rng) => rng.gen_range(0, i + 1),
+                None => gen_range(0, i + 1),
+            };
             self.pieces.swap(i, j);
         }
         self.index = 0;
@@ -44,6 +64,12 @@ impl PieceBag {
     }
 }
 
+impl Randomizer for PieceBag {
+    fn next_piece(&mut self) -> TetrominoType {
+        PieceBag::next_piece(self)
+    }
+}
+
 impl Default for PieceBag {
     fn default() -> Self {
         Self::new()
@@ -79,4 +105,15 @@ mod tests {
 
         assert!(bag.next_piece() as i32 >= 0);
     }
+
+    #[test]
+    fn test_seeded_bags_repeat() {
+        let mut first = PieceBag::seeded(42);
+        let mut second = PieceBag::seeded(42);
+        let mut other = PieceBag::seeded(7);
+
+        let sequence: Vec<_> = (0..21).map(|_| first.next_piece()).collect();
+        assert!((0..21).all(|i| second.next_piece() == sequence[i]));
+        assert!((0..21).any(|i| other.next_piece() != sequence[i]));
+    }
 }
@@ -1,6 +1,11 @@
 pub mod bag;
 pub mod piece;
+pub mod randomizer;
+pub mod sequence;
 pub mod types;
 
 pub use bag::PieceBag;
 pub use piece::Tetromino;
+pub use randomizer::Randomizer;
+pub use sequence::PieceSequence;
+pub use types::TetrominoType;
@@ -1,49 +1,51 @@
 use super::types::TetrominoType;
 use crate::config::BOARD_WIDTH;
 
-#[derive(Clone)]
+/// A falling piece: its type, rotation state and position. The cells come
+/// from static rotation tables, so copying a piece is free.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Tetromino {
-    pub shape: Vec<Vec<bool>>,
-    #[allow(dead_code)]
     pub piece_type: TetrominoType,
+    pub rotation: u8,
     pub x: i32,
     pub y: i32,
 }
 
 impl Tetromino {
+    /// A piece at the spawn position of a standard-width board.
     pub fn new(piece_type: TetrominoType) -> Self {
+        Self::spawn(piece_type, BOARD_WIDTH)
+    }
+
+    /// A piece at the spawn position of a board `board_width` columns wide.
+    pub fn spawn(piece_type: TetrominoType, board_width: i32) -> Self {
         Tetromino {
-            shape: piece_type.get_shape(),
             piece_type,
-            x: BOARD_WIDTH / 2 - 2,
+            rotation: 0,
+            x: board_width / 2 - 2,
             y: 0,
         }
     }
 
+    /// Rotate clockwise to the next rotation state.
     pub fn rotate(&mut self) {
-        let rows = self.shape.len();
-        let cols = self.shape[0].len();
-        let mut rotated = vec![vec![false; rows]; cols];
+        self.rotation = (self.rotation + 1) % 4;
+    }
 
-        for i in 0..rows {
-            for j in 0..cols {
-                rotated[j][rows - 1 - i] = self.shape[i][j];
-            }
-        }
+    /// Rotate counter-clockwise to the previous rotation state.
+    pub fn rotate_ccw(&mut self) {
+        self.rotation = (self.rotation + 3) % 4;
+    }
 
-        self.shape = rotated;
+    /// Cell offsets of the current rotation state, relative to `x`/`y`.
+    pub fn cells(&self) -> &'static [(i8, i8); 4] {
+        self.piece_type.cells(self.rotation)
     }
 
-    pub fn get_blocks(&self) -> Vec<(i32, i32)> {
-        let mut blocks = Vec::new();
-        for (row, line) in self.shape.iter().enumerate() {
-            for (col, &filled) in line.iter().enumerate() {
-                if filled {
-                    blocks.push((self.x + col as i32, self.y + row as i32));
-                }
-            }
-        }
-        blocks
+    /// Board coordinates of the four cells this piece occupies.
+    pub fn get_blocks(&self) -> [(i32, i32); 4] {
+        self.cells()
+            .map(|(dx, dy)| (self.x + dx as i32, self.y + dy as i32))
     }
 
     pub fn move_by(&mut self, dx: i32, dy: i32) {
@@ -51,9 +53,8 @@ impl Tetromino {
         self.y += dy;
     }
 
-    pub fn reset_position(&mut self) {
-        self.x = BOARD_WIDTH / 2 - 2;
-        self.y = 0;
+    pub fn reset_position(&mut self, board_width: i32) {
+        *self = Self::spawn(self.piece_type, board_width);
     }
 }
 
@@ -65,7 +66,7 @@ mod tests {
     fn test_tetromino_creation() {
         let piece = Tetromino::new(TetrominoType::I);
         assert_eq!(piece.piece_type, TetrominoType::I);
-        assert!(!piece.shape.is_empty());
+        assert_eq!(piece.rotation, 0);
     }
 
     #[test]
@@ -82,15 +83,16 @@ mod tests {
     #[test]
     fn test_tetromino_rotation() {
         let mut piece = Tetromino::new(TetrominoType::I);
-        let initial_shape = piece.shape.clone();
+        let initial_blocks = piece.get_blocks();
 
         piece.rotate();
-        assert_ne!(piece.shape, initial_shape);
+        assert_ne!(piece.get_blocks(), initial_blocks);
 
         piece.rotate();
         piece.rotate();
         piece.rotate();
-        assert_eq!(piece.shape.len(), initial_shape.len());
+        assert_eq!(piece.rotation, 0);
+        assert_eq!(piece.get_blocks(), initial_blocks);
     }
 
     #[test]
@@ -100,4 +102,50 @@ mod tests {
 
         assert_eq!(blocks.len(), 4);
     }
+
+    #[test]
+    fn test_rotation_states_have_four_distinct_cells() {
+        for piece_type in TetrominoType::all() {
+            for rotation in 0..4 {
+                let cells = piece_type.cells(rotation);
+                for (i, cell) in cells.iter().enumerate() {
+                    assert!(!cells[i + 1..].contains(cell), "{piece_type:?} r{rotation}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_spawn_is_centered() {
+        assert_eq!(Tetromino::spawn(TetrominoType::T, 10).x, 3);
+        assert_eq!(Tetromino::spawn(TetrominoType::I, 4).x, 0);
+        assert_eq!(Tetromino::spawn(TetrominoType::O, 20).x, 8);
+    }
+
+    #[test]
+    fn test_rotate_ccw_undoes_rotate() {
+        let mut piece = Tetromino::new(TetrominoType::L);
+        piece.rotate_ccw();
+        assert_eq!(piece.rotation, 3);
+
+        piece.rotate();
+        assert_eq!(
+            piece.get_blocks(),
+            Tetromino::new(TetrominoType::L).get_blocks()
+        );
+    }
+
+    #[test]
+    fn test_kicks_mirror_between_directions() {
+        for piece_type in TetrominoType::all() {
+            for from in 0..4u8 {
+                let to = (from + 1) % 4;
+                let forward = piece_type.kicks(from, to);
+                let back = piece_type.kicks(to, from);
+                for (a, b) in forward.iter().zip(back) {
+                    assert_eq!((a.0, a.1), (-b.0, -b.1), "{piece_type:?} {from}->{to}");
+                }
+            }
+        }
+    }
 }
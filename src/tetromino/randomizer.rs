@@ -0,0 +1,7 @@
+use super::types::TetrominoType;
+
+/// Source of the piece sequence. Game modes pick the randomizer they want;
+/// the seven-bag `PieceBag` is the default.
+pub trait Randomizer: Send {
+    fn next_piece(&mut self) -> TetrominoType;
+}
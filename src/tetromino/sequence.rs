@@ -0,0 +1,42 @@
+use super::randomizer::Randomizer;
+use super::types::TetrominoType;
+
+/// A fixed, authored piece order, as used by puzzles. Once the list runs
+/// out it starts again from the top.
+pub struct PieceSequence {
+    pieces: Vec<TetrominoType>,
+    index: usize,
+}
+
+impl PieceSequence {
+    pub fn new(pieces: Vec<TetrominoType>) -> Self {
+        assert!(
+            !pieces.is_empty(),
+            "a piece sequence needs at least one piece"
+        );
+        PieceSequence { pieces, index: 0 }
+    }
+}
+
+impl Randomizer for PieceSequence {
+    fn next_piece(&mut self) -> TetrominoType {
+        let piece = self.pieces[self.index];
+        self.index = (self.index + 1) % self.pieces.len();
+        piece
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_plays_in_order_and_repeats() {
+        let mut sequence = PieceSequence::new(vec![TetrominoType::T, TetrominoType::I]);
+        let pieces: Vec<_> = (0..3).map(|_| sequence.next_piece()).collect();
+        assert_eq!(
+            pieces,
+            [TetrominoType::T, TetrominoType::I, TetrominoType::T]
+        );
+    }
+}
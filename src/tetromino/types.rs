@@ -22,15 +22,123 @@ impl TetrominoType {
         ]
     }
 
-    pub fn get_shape(&self) -> Vec<Vec<bool>> {
+    /// The piece named by its letter, e.g. `'T'`.
+    pub fn from_letter(letter: char) -> Option<TetrominoType> {
+        Self::all()
+            .into_iter()
+            .find(|piece| piece.letter() == letter.to_ascii_uppercase())
+    }
+
+    pub fn letter(&self) -> char {
+        match self {
+            TetrominoType::I => 'I',
+            TetrominoType::O => 'O',
+            TetrominoType::T => 'T',
+            TetrominoType::S => 'S',
+            TetrominoType::Z => 'Z',
+            TetrominoType::J => 'J',
+            TetrominoType::L => 'L',
+        }
+    }
+
+    /// Cell offsets of this piece in the given rotation state
+    /// (0 = spawn, then clockwise), relative to the piece position.
+    pub fn cells(&self, rotation: u8) -> &'static [(i8, i8); 4] {
+        let states = match self {
+            TetrominoType::I => &I_STATES,
+            TetrominoType::O => &O_STATES,
+            TetrominoType::T => &T_STATES,
+            TetrominoType::S => &S_STATES,
+            TetrominoType::Z => &Z_STATES,
+            TetrominoType::J => &J_STATES,
+            TetrominoType::L => &L_STATES,
+        };
+        &states[(rotation % 4) as usize]
+    }
+
+    /// SRS wall kick offsets to try, in order, when rotating from one state
+    /// to an adjacent one. Offsets use the board's y-down coordinates.
+    pub fn kicks(&self, from: u8, to: u8) -> &'static [(i8, i8); 5] {
+        let (from, to) = (from % 4, to % 4);
+        let clockwise = (from + 1) % 4 == to;
+        let index = from as usize * 2 + usize::from(!clockwise);
+
         match self {
-            TetrominoType::I => vec![vec![true, true, true, true]],
-            TetrominoType::O => vec![vec![true, true], vec![true, true]],
-            TetrominoType::T => vec![vec![false, true, false], vec![true, true, true]],
-            TetrominoType::S => vec![vec![false, true, true], vec![true, true, false]],
-            TetrominoType::Z => vec![vec![true, true, false], vec![false, true, true]],
-            TetrominoType::J => vec![vec![true, false, false], vec![true, true, true]],
-            TetrominoType::L => vec![vec![false, false, true], vec![true, true, true]],
+            TetrominoType::O => &NO_KICKS,
+            TetrominoType::I => &I_KICKS[index],
+            _ => &JLSTZ_KICKS[index],
         }
     }
 }
+
+const NO_KICKS: [(i8, i8); 5] = [(0, 0); 5];
+
+// Indexed by `from * 2`, plus one for counter-clockwise turns:
+// 0->R, 0->L, R->2, R->0, 2->L, 2->R, L->0, L->2.
+const JLSTZ_KICKS: [[(i8, i8); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+];
+
+const I_KICKS: [[(i8, i8); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],
+    [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],
+    [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],
+    [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)],
+    [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)],
+    [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],
+    [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],
+    [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],
+];
+
+// SRS rotation states. The I piece is shifted up one row so that it spawns
+// on the top row like the other pieces.
+const I_STATES: [[(i8, i8); 4]; 4] = [
+    [(0, 0), (1, 0), (2, 0), (3, 0)],
+    [(2, -1), (2, 0), (2, 1), (2, 2)],
+    [(0, 1), (1, 1), (2, 1), (3, 1)],
+    [(1, -1), (1, 0), (1, 1), (1, 2)],
+];
+
+const O_STATES: [[(i8, i8); 4]; 4] = [[(1, 0), (2, 0), (1, 1), (2, 1)]; 4];
+
+const T_STATES: [[(i8, i8); 4]; 4] = [
+    [(1, 0), (0, 1), (1, 1), (2, 1)],
+    [(1, 0), (1, 1), (2, 1), (1, 2)],
+    [(0, 1), (1, 1), (2, 1), (1, 2)],
+    [(1, 0), (0, 1), (1, 1), (1, 2)],
+];
+
+const S_STATES: [[(i8, i8); 4]; 4] = [
+    [(1, 0), (2, 0), (0, 1), (1, 1)],
+    [(1, 0), (1, 1), (2, 1), (2, 2)],
+    [(1, 1), (2, 1), (0, 2), (1, 2)],
+    [(0, 0), (0, 1), (1, 1), (1, 2)],
+];
+
+const Z_STATES: [[(i8, i8); 4]; 4] = [
+    [(0, 0), (1, 0), (1, 1), (2, 1)],
+    [(2, 0), (1, 1), (2, 1), (1, 2)],
+    [(0, 1), (1, 1), (1, 2), (2, 2)],
+    [(1, 0), (0, 1), (1, 1), (0, 2)],
+];
+
+const J_STATES: [[(i8, i8); 4]; 4] = [
+    [(0, 0), (0, 1), (1, 1), (2, 1)],
+    [(1, 0), (2, 0), (1, 1), (1, 2)],
+    [(0, 1), (1, 1), (2, 1), (2, 2)],
+    [(1, 0), (1, 1), (0, 2), (1, 2)],
+];
+
+const L_STATES: [[(i8, i8); 4]; 4] = [
+    [(2, 0), (0, 1), (1, 1), (2, 1)],
+    [(1, 0), (1, 1), (1, 2), (2, 2)],
+    [(0, 1), (1, 1), (2, 1), (0, 2)],
+    [(0, 0), (1, 0), (1, 1), (1, 2)],
+];